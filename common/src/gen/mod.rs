@@ -0,0 +1,31 @@
+//! Seeded random generators of valid puzzle inputs, to stress-test the parsers and to measure how
+//! the solutions scale beyond the size of the official inputs. When it's cheap to do so, the
+//! generators also return the expected answers, which are known by construction.
+
+pub mod modules;
+pub mod pipes;
+pub mod platform;
+pub mod springs;
+pub mod workflows;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Deterministic RNG, so that a failing input can be reproduced from its seed.
+pub fn rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// Distinct lowercase labels (`prefix` followed by a base 26 number), in a predictable order.
+pub fn label(prefix: &str, idx: usize) -> String {
+    let mut label = prefix.to_owned();
+    let mut n = idx;
+    loop {
+        label.push((b'a' + (n % 26) as u8) as char);
+        n /= 26;
+        if n == 0 {
+            break;
+        }
+    }
+    label
+}
@@ -0,0 +1,105 @@
+use itertools::Itertools;
+use lcmx::lcmx;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Day 20 input, with the number of button presses before `rx` first receives a low pulse.
+pub struct ModuleNetwork {
+    pub input: String,
+    pub presses_until_rx: u64,
+}
+
+/// Same structure as the official input: the broadcaster starts `counters` binary counters of
+/// `bits` flip-flops each. Each counter has a conjunction, which receives from the flip-flops of
+/// the 1 bits of its period and resets the counter (by sending to the others and to the lowest
+/// bit) when it reaches that period. The conjunction then sends a high pulse through an inverter
+/// to the final conjunction, which only sends a low pulse to `rx` once all the counters reset
+/// during the same press.
+pub fn module_network(rng: &mut impl Rng, counters: usize, bits: usize) -> ModuleNetwork {
+    assert!(
+        (2..64).contains(&bits),
+        "unsupported counter size: {}",
+        bits
+    );
+    // At least two letters, like the official labels (one more than needed, in case of "rx")
+    let mut labels = (0..counters * (bits + 2) + 2)
+        .map(|i| super::label("", i + 26))
+        .filter(|l| l != "rx")
+        .collect_vec();
+    labels.shuffle(rng);
+    let mut labels = labels.into_iter();
+    let final_conjunction = labels.next().unwrap();
+
+    let mut modules = Vec::new();
+    let mut first_bits = Vec::new();
+    let mut periods = Vec::new();
+    for _ in 0..counters {
+        // Highest and lowest bits always set
+        let period = (1 << (bits - 1)) | rng.gen_range(0..1u64 << (bits - 1)) | 1;
+        periods.push(period);
+        let flip_flops = (&mut labels).take(bits).collect_vec();
+        let conjunction = labels.next().unwrap();
+        let inverter = labels.next().unwrap();
+
+        let mut conjunction_targets = vec![flip_flops[0].clone()];
+        for (bit, flip_flop) in flip_flops.iter().enumerate() {
+            let mut targets = flip_flops.get(bit + 1).into_iter().cloned().collect_vec();
+            if period & (1 << bit) != 0 {
+                targets.push(conjunction.clone());
+            } else {
+                conjunction_targets.push(flip_flop.clone());
+            }
+            targets.shuffle(rng);
+            modules.push(format!("%{} -> {}", flip_flop, targets.join(", ")));
+        }
+        conjunction_targets.push(inverter.clone());
+        conjunction_targets.shuffle(rng);
+        modules.push(format!(
+            "&{} -> {}",
+            conjunction,
+            conjunction_targets.join(", ")
+        ));
+        modules.push(format!("&{} -> {}", inverter, final_conjunction));
+        first_bits.push(flip_flops[0].clone());
+    }
+    modules.push(format!("broadcaster -> {}", first_bits.join(", ")));
+    modules.push(format!("&{} -> rx", final_conjunction));
+    modules.shuffle(rng);
+
+    ModuleNetwork {
+        input: modules.join("\n"),
+        presses_until_rx: lcmx(&periods).unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::rng;
+
+    // The presses until `rx` are checked against the day 20 solution, in `day_20`
+    #[test]
+    fn test_module_network() {
+        for seed in 0..5 {
+            let network = module_network(&mut rng(seed), 3, 5);
+            let lines = network.input.lines().collect_vec();
+            assert_eq!(lines.len(), 3 * (5 + 2) + 2);
+            assert_eq!(lines.iter().filter(|l| l.starts_with('%')).count(), 3 * 5);
+            assert_eq!(lines.iter().filter(|l| l.ends_with("-> rx")).count(), 1);
+            assert!(lines.iter().any(|l| l.starts_with("broadcaster -> ")));
+            let labels = lines
+                .iter()
+                .map(|l| {
+                    l.split(" -> ")
+                        .next()
+                        .unwrap()
+                        .trim_start_matches(['%', '&'])
+                })
+                .collect_vec();
+            assert!(labels.iter().all_unique());
+            // Periods are odd, between 2^4 and 2^5
+            assert_eq!(network.presses_until_rx % 2, 1);
+            assert!(network.presses_until_rx >= 1 << 4);
+        }
+    }
+}
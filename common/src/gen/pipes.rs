@@ -0,0 +1,207 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Day 10 input, with its answers.
+pub struct PipeMaze {
+    pub input: String,
+    /// Steps to the farthest point of the loop
+    pub farthest: usize,
+    /// Tiles enclosed by the loop
+    pub enclosed: usize,
+}
+
+const N: usize = 0;
+const E: usize = 1;
+const S: usize = 2;
+const W: usize = 3;
+
+/// The loop is the outline of a random blob of `width × height` cells, drawn at twice the scale
+/// (so the map is `(2 * width + 1) × (2 * height + 1)` tiles). The blob grows one cell at a time,
+/// never touching itself diagonally nor enclosing a hole, so that its outline is a simple loop.
+/// Tiles outside the loop are junk pipes with probability `junk_density`.
+pub fn pipe_maze(rng: &mut impl Rng, width: usize, height: usize, junk_density: f64) -> PipeMaze {
+    let blob = grow_blob(rng, width, height);
+    let in_blob = |x: i64, y: i64| {
+        (0..width as i64).contains(&x)
+            && (0..height as i64).contains(&y)
+            && blob[y as usize * width + x as usize]
+    };
+
+    // Connections of each tile of the map (cell (x, y) spans tiles 2x..=2x+2, 2y..=2y+2)
+    let (map_width, map_height) = (2 * width + 1, 2 * height + 1);
+    let mut connections = vec![[false; 4]; map_width * map_height];
+    for ty in 0..map_height {
+        for tx in 0..map_width {
+            let (cx, cy) = ((tx / 2) as i64, (ty / 2) as i64);
+            let c = &mut connections[ty * map_width + tx];
+            if ty % 2 == 0 {
+                // Horizontal cell edge between cells (cx, cy - 1) and (cx, cy)
+                let edge = |cx: i64| in_blob(cx, cy - 1) != in_blob(cx, cy);
+                c[E] = tx + 1 < map_width && edge(cx);
+                c[W] = tx > 0 && edge(((tx - 1) / 2) as i64);
+            }
+            if tx % 2 == 0 {
+                let edge = |cy: i64| in_blob(cx - 1, cy) != in_blob(cx, cy);
+                c[S] = ty + 1 < map_height && edge(cy);
+                c[N] = ty > 0 && edge(((ty - 1) / 2) as i64);
+            }
+        }
+    }
+
+    let on_loop = |i: usize| connections[i].contains(&true);
+    let loop_len = (0..connections.len()).filter(|&i| on_loop(i)).count();
+    // Not on the loop, and only surrounded by blob cells
+    let enclosed = (0..connections.len())
+        .filter(|&i| {
+            let (tx, ty) = ((i % map_width) as i64, (i / map_width) as i64);
+            let xs = if tx % 2 == 0 {
+                vec![tx / 2 - 1, tx / 2]
+            } else {
+                vec![tx / 2]
+            };
+            let ys = if ty % 2 == 0 {
+                vec![ty / 2 - 1, ty / 2]
+            } else {
+                vec![ty / 2]
+            };
+            !on_loop(i) && xs.iter().all(|&x| ys.iter().all(|&y| in_blob(x, y)))
+        })
+        .count();
+
+    let loop_tiles = (0..connections.len())
+        .filter(|&i| on_loop(i))
+        .collect::<Vec<_>>();
+    let start = *loop_tiles.choose(rng).unwrap();
+    let next_to_start = |i: usize| {
+        let (dx, dy) = (
+            (i % map_width).abs_diff(start % map_width),
+            (i / map_width).abs_diff(start / map_width),
+        );
+        dx + dy == 1
+    };
+
+    let mut input = String::with_capacity((map_width + 1) * map_height);
+    for (i, c) in connections.iter().enumerate() {
+        let tile = if i == start {
+            'S'
+        } else if on_loop(i) {
+            pipe(c)
+        } else if !next_to_start(i) && rng.gen_bool(junk_density) {
+            // Junk next to the start could make it ambiguous
+            *['|', '-', 'L', 'J', '7', 'F'].choose(rng).unwrap()
+        } else {
+            '.'
+        };
+        input.push(tile);
+        if i % map_width == map_width - 1 && i + 1 < connections.len() {
+            input.push('\n');
+        }
+    }
+
+    PipeMaze { input, farthest: loop_len / 2, enclosed }
+}
+
+fn pipe(connections: &[bool; 4]) -> char {
+    match connections {
+        [true, false, true, false] => '|',
+        [false, true, false, true] => '-',
+        [true, true, false, false] => 'L',
+        [true, false, false, true] => 'J',
+        [false, false, true, true] => '7',
+        [false, true, true, false] => 'F',
+        _ => panic!("not a loop tile: {:?}", connections),
+    }
+}
+
+fn grow_blob(rng: &mut impl Rng, width: usize, height: usize) -> Vec<bool> {
+    let mut blob = vec![false; width * height];
+    let target = (width * height).div_ceil(2).max(1);
+    let first = rng.gen_range(0..width * height);
+    blob[first] = true;
+    let mut size = 1;
+    let mut candidates = neighbours(first, width, height);
+    while size < target && !candidates.is_empty() {
+        let candidate = candidates.swap_remove(rng.gen_range(0..candidates.len()));
+        if !blob[candidate] && can_grow(&blob, candidate, width, height) {
+            blob[candidate] = true;
+            size += 1;
+            candidates.extend(
+                neighbours(candidate, width, height)
+                    .into_iter()
+                    .filter(|&n| !blob[n]),
+            );
+        }
+    }
+    blob
+}
+
+fn neighbours(i: usize, width: usize, height: usize) -> Vec<usize> {
+    let (x, y) = (i % width, i / width);
+    let mut neighbours = Vec::with_capacity(4);
+    if x > 0 {
+        neighbours.push(i - 1);
+    }
+    if x + 1 < width {
+        neighbours.push(i + 1);
+    }
+    if y > 0 {
+        neighbours.push(i - width);
+    }
+    if y + 1 < height {
+        neighbours.push(i + width);
+    }
+    neighbours
+}
+
+/// The blob cells around the new cell must form a single arc of its 8 surrounding cells, and
+/// must not touch it only diagonally.
+fn can_grow(blob: &[bool], i: usize, width: usize, height: usize) -> bool {
+    let (x, y) = ((i % width) as i64, (i / width) as i64);
+    let ring = [
+        (0, -1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+        (0, 1),
+        (-1, 1),
+        (-1, 0),
+        (-1, -1),
+    ]
+    .map(|(dx, dy)| {
+        let (x, y) = (x + dx, y + dy);
+        (0..width as i64).contains(&x)
+            && (0..height as i64).contains(&y)
+            && blob[y as usize * width + x as usize]
+    });
+    let arcs = (0..8).filter(|&k| ring[k] && !ring[(k + 1) % 8]).count();
+    let diagonal_touch = [1, 3, 5, 7]
+        .into_iter()
+        .any(|k| ring[k] && !ring[k - 1] && !ring[(k + 1) % 8]);
+    arcs == 1 && !diagonal_touch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::rng;
+
+    #[test]
+    fn test_pipe_maze() {
+        // A single cell, with the start anywhere on its outline
+        let maze = pipe_maze(&mut rng(1), 1, 1, 0.0);
+        let expected = "F-7\n|.|\nL-J";
+        assert!(maze
+            .input
+            .chars()
+            .zip(expected.chars())
+            .all(|(c, e)| c == e || c == 'S'));
+        assert_eq!(maze.farthest, 4);
+        assert_eq!(maze.enclosed, 1);
+
+        let maze = pipe_maze(&mut rng(2), 20, 10, 0.5);
+        let lines = maze.input.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 21);
+        assert!(lines.iter().all(|l| l.chars().count() == 41));
+        assert_eq!(maze.input.matches('S').count(), 1);
+    }
+}
@@ -0,0 +1,45 @@
+use rand::Rng;
+
+/// Day 14 input: a platform with round rocks (`O`) and cube-shaped rocks (`#`), each tile being
+/// independently one or the other with the given probabilities.
+pub fn platform(
+    rng: &mut impl Rng,
+    width: usize,
+    height: usize,
+    round_density: f64,
+    cube_density: f64,
+) -> String {
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| {
+                    let r = rng.gen::<f64>();
+                    if r < round_density {
+                        'O'
+                    } else if r < round_density + cube_density {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::rng;
+
+    #[test]
+    fn test_platform() {
+        let platform = platform(&mut rng(14), 100, 50, 0.2, 0.1);
+        let lines = platform.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 50);
+        assert!(lines.iter().all(|l| l.len() == 100));
+        let round_rocks = platform.matches('O').count();
+        assert!((800..1200).contains(&round_rocks));
+    }
+}
@@ -0,0 +1,159 @@
+use itertools::Itertools;
+use rand::Rng;
+
+/// Day 12 input, with the number of arrangements of each record.
+pub struct SpringRecords {
+    pub input: String,
+    pub arrangements: Vec<u64>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SpringParams {
+    pub records: usize,
+    pub max_segments: usize,
+    pub max_groups_per_segment: usize,
+    pub max_group_len: usize,
+    /// Maximum number of extra operational springs in a segment
+    pub max_slack: usize,
+}
+
+impl Default for SpringParams {
+    /// About the size of the official input
+    fn default() -> Self {
+        SpringParams {
+            records: 1000,
+            max_segments: 3,
+            max_groups_per_segment: 3,
+            max_group_len: 4,
+            max_slack: 4,
+        }
+    }
+}
+
+/// A run of springs between operational springs.
+#[derive(Copy, Clone, Debug)]
+enum Segment {
+    Unknown(usize),
+    Damaged(usize),
+}
+
+/// Each record is made of segments of unknown springs, or of damaged springs, separated by
+/// operational springs. The groups are generated segment by segment, so that there is at least
+/// one arrangement, but the groups of a segment may also fit in the other segments: see
+/// `count_arrangements` for the actual count.
+pub fn spring_records(rng: &mut impl Rng, params: SpringParams) -> SpringRecords {
+    let mut lines = Vec::with_capacity(params.records);
+    let mut arrangements = Vec::with_capacity(params.records);
+    for _ in 0..params.records {
+        let mut segments = Vec::new();
+        let mut groups = Vec::new();
+        for _ in 0..rng.gen_range(1..=params.max_segments) {
+            if rng.gen_bool(0.2) {
+                let len = rng.gen_range(1..=params.max_group_len);
+                segments.push(Segment::Damaged(len));
+                groups.push(len);
+            } else {
+                let segment_groups = (0..rng.gen_range(1..=params.max_groups_per_segment))
+                    .map(|_| rng.gen_range(1..=params.max_group_len))
+                    .collect_vec();
+                let slack = rng.gen_range(0..=params.max_slack);
+                let len = segment_groups.iter().sum::<usize>() + segment_groups.len() - 1 + slack;
+                segments.push(Segment::Unknown(len));
+                groups.extend(segment_groups);
+            }
+        }
+
+        let mut springs = ".".repeat(rng.gen_range(0..=1));
+        for (i, segment) in segments.iter().enumerate() {
+            if i > 0 {
+                springs.push_str(&".".repeat(rng.gen_range(1..=2)));
+            }
+            match *segment {
+                Segment::Unknown(len) => springs.push_str(&"?".repeat(len)),
+                Segment::Damaged(len) => springs.push_str(&"#".repeat(len)),
+            }
+        }
+        springs.push_str(&".".repeat(rng.gen_range(0..=1)));
+        lines.push(format!("{} {}", springs, groups.iter().join(",")));
+        arrangements.push(count_arrangements(&segments, &groups));
+    }
+    SpringRecords { input: lines.join("\n"), arrangements }
+}
+
+/// Sum, over all the ways to split the (ordered) groups between the segments, of the product of
+/// the arrangements within each segment. A damaged segment must hold exactly one group of its
+/// length. `k` groups with `s` extra operational springs in an unknown segment can be arranged in
+/// `C(s + k, k)` ways (`s` springs to share between the `k + 1` gaps).
+fn count_arrangements(segments: &[Segment], groups: &[usize]) -> u64 {
+    // ways[g]: ways to place the first g groups in the segments so far
+    let mut ways = vec![0u64; groups.len() + 1];
+    ways[0] = 1;
+    for segment in segments {
+        let mut next_ways = vec![0u64; groups.len() + 1];
+        for (start, &w) in ways.iter().enumerate().filter(|(_, &w)| w > 0) {
+            match *segment {
+                Segment::Damaged(len) => {
+                    if groups.get(start) == Some(&len) {
+                        next_ways[start + 1] += w;
+                    }
+                }
+                Segment::Unknown(len) => {
+                    next_ways[start] += w;
+                    for end in start + 1..=groups.len() {
+                        let k = end - start;
+                        let min_len = groups[start..end].iter().sum::<usize>() + k - 1;
+                        if min_len > len {
+                            break;
+                        }
+                        next_ways[end] += w * binomial(len - min_len + k, k);
+                    }
+                }
+            }
+        }
+        ways = next_ways;
+    }
+    ways[groups.len()]
+}
+
+fn binomial(n: usize, k: usize) -> u64 {
+    (0..k).fold(1, |acc, i| acc * (n - i) as u64 / (i + 1) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::rng;
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(binomial(5, 0), 1);
+        assert_eq!(binomial(5, 2), 10);
+        assert_eq!(binomial(10, 5), 252);
+    }
+
+    #[test]
+    fn test_count_arrangements() {
+        use Segment::*;
+        assert_eq!(count_arrangements(&[Unknown(3)], &[1, 1]), 1);
+        assert_eq!(count_arrangements(&[Unknown(3), Damaged(3)], &[1, 1, 3]), 1);
+        // ????.?? 1,1: both in the first (3), one in each (4 × 2), not both in the second
+        assert_eq!(
+            count_arrangements(&[Unknown(4), Unknown(2)], &[1, 1]),
+            3 + 8
+        );
+        assert_eq!(count_arrangements(&[Damaged(2)], &[1]), 0);
+    }
+
+    #[test]
+    fn test_spring_records() {
+        let params = SpringParams { records: 50, ..SpringParams::default() };
+        let records = spring_records(&mut rng(12), params);
+        assert_eq!(records.input.lines().count(), 50);
+        assert_eq!(records.arrangements.len(), 50);
+        assert!(records.input.lines().all(|l| {
+            let (springs, groups) = l.split_once(' ').unwrap();
+            springs.chars().all(|c| ".#?".contains(c))
+                && groups.split(',').all(|g| g.parse::<usize>().is_ok())
+        }));
+    }
+}
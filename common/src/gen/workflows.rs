@@ -0,0 +1,115 @@
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const CATEGORIES: [&str; 4] = ["x", "m", "a", "s"];
+const MAX_RATING: usize = 4000;
+
+#[derive(Copy, Clone, Debug)]
+pub struct WorkflowParams {
+    /// Maximum number of workflows between "in" and an accepted or rejected result
+    pub depth: usize,
+    /// Maximum number of conditional rules in a workflow (before the default rule)
+    pub max_rules: usize,
+    /// Probability for the result of a rule to be another workflow, while `depth` allows it
+    pub chain_probability: f64,
+}
+
+impl Default for WorkflowParams {
+    /// About the size of the official input
+    fn default() -> Self {
+        WorkflowParams { depth: 8, max_rules: 3, chain_probability: 0.6 }
+    }
+}
+
+/// Day 19 workflows, forming a tree rooted at "in" (each workflow is the result of a single rule,
+/// so there is no cycle), in a random order.
+pub fn workflows(rng: &mut impl Rng, params: WorkflowParams) -> String {
+    let mut workflows = Vec::new();
+    add_workflow(rng, &params, "in".to_owned(), params.depth, &mut workflows);
+    workflows.shuffle(rng);
+    workflows.join("\n")
+}
+
+fn add_workflow<R: Rng>(
+    rng: &mut R,
+    params: &WorkflowParams,
+    label: String,
+    depth: usize,
+    workflows: &mut Vec<String>,
+) {
+    // Reserve the slot of this workflow, so that nested workflows get distinct labels
+    let idx = workflows.len();
+    workflows.push(String::new());
+    let result = |rng: &mut R, workflows: &mut Vec<String>| {
+        if depth > 1 && rng.gen_bool(params.chain_probability) {
+            let child = super::label("w", workflows.len());
+            add_workflow(rng, params, child.clone(), depth - 1, workflows);
+            child
+        } else if rng.gen_bool(0.5) {
+            "A".to_owned()
+        } else {
+            "R".to_owned()
+        }
+    };
+    let rules = (0..rng.gen_range(1..=params.max_rules))
+        .map(|_| {
+            let category = CATEGORIES.choose(rng).unwrap();
+            let operator = if rng.gen_bool(0.5) { '<' } else { '>' };
+            let value = rng.gen_range(1..=MAX_RATING);
+            format!(
+                "{}{}{}:{}",
+                category,
+                operator,
+                value,
+                result(rng, workflows)
+            )
+        })
+        .collect_vec();
+    let default = result(rng, workflows);
+    workflows[idx] = format!("{}{{{},{}}}", label, rules.join(","), default);
+}
+
+/// Day 19 parts, with uniformly random ratings.
+pub fn parts(rng: &mut impl Rng, count: usize) -> String {
+    (0..count)
+        .map(|_| {
+            let ratings = CATEGORIES
+                .iter()
+                .map(|c| format!("{}={}", c, rng.gen_range(1..=MAX_RATING)))
+                .join(",");
+            format!("{{{}}}", ratings)
+        })
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::rng;
+
+    #[test]
+    fn test_workflows() {
+        let params = WorkflowParams { depth: 1, max_rules: 2, chain_probability: 1.0 };
+        let workflows = workflows(&mut rng(19), params);
+        assert!(workflows.starts_with("in{"));
+        assert_eq!(workflows.lines().count(), 1);
+
+        let workflows = super::workflows(&mut rng(19), WorkflowParams::default());
+        let labels = workflows
+            .lines()
+            .map(|l| l.split_once('{').unwrap().0)
+            .collect_vec();
+        assert!(labels.contains(&"in"));
+        assert!(labels.iter().all_unique());
+    }
+
+    #[test]
+    fn test_parts() {
+        let parts = parts(&mut rng(19), 3);
+        assert_eq!(parts.lines().count(), 3);
+        assert!(parts
+            .lines()
+            .all(|l| l.starts_with("{x=") && l.ends_with('}')));
+    }
+}
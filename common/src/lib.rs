@@ -4,8 +4,17 @@ pub use lazy_static;
 pub use lcmx;
 pub use maplit;
 pub use ndarray;
+pub use num_bigint;
+pub use num_traits;
+pub use rand;
 pub use regex;
 
+pub mod gen;
+pub mod snapshot;
+mod solution;
+
+pub use solution::Solution;
+
 use std::time::Instant;
 
 pub fn time_execution<T>(name: &str, f: impl Fn() -> T) -> T {
@@ -0,0 +1,280 @@
+//! Snapshot tests: a value is serialised to a stable text form, and compared to a checked-in
+//! `snapshots/<name>.snap` file in the crate which runs the test. Run the tests with
+//! `UPDATE_SNAPSHOTS=1` to create missing snapshots and to overwrite the ones which changed.
+
+use itertools::{EitherOrBoth, Itertools};
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+
+const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+const MAX_MAP_WIDTH: usize = 80;
+
+/// Compare the stable text form of a `Debug` value to a snapshot of the current crate.
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $value:expr) => {
+        $crate::snapshot::assert_snapshot_file(
+            env!("CARGO_MANIFEST_DIR"),
+            $name,
+            &$crate::snapshot::to_snapshot(&$value),
+        )
+    };
+}
+
+/// The pretty `Debug` form of a value, made stable and a bit more compact:
+/// - the entries of maps and sets (blocks in `{}` without a type name) are sorted (numbers by
+///   value), since the iteration order of a `HashMap` or `HashSet` changes from one run to the next
+/// - blocks other than structs are written on a single line when all their entries are simple
+///   values (e.g. each row of a grid gets its own line)
+pub fn to_snapshot(value: &impl Debug) -> String {
+    let debug = format!("{:#?}", value);
+    let lines = debug.lines().collect_vec();
+    normalise(&lines).join("\n") + "\n"
+}
+
+pub fn assert_snapshot_file(manifest_dir: &str, name: &str, actual: &str) {
+    let path = Path::new(manifest_dir)
+        .join("snapshots")
+        .join(format!("{}.snap", name));
+    let expected = fs::read_to_string(&path).ok();
+    if expected.as_deref() == Some(actual) {
+        return;
+    }
+
+    let update = std::env::var_os(UPDATE_VAR).is_some_and(|v| v != "0");
+    if update {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual)
+            .unwrap_or_else(|e| panic!("Cannot write snapshot {}: {}", path.display(), e));
+        eprintln!("Updated snapshot {}", path.display());
+        return;
+    }
+    match expected {
+        None => panic!(
+            "Missing snapshot {} (run the tests with {}=1 to create it)",
+            path.display(),
+            UPDATE_VAR
+        ),
+        Some(expected) => panic!(
+            "Snapshot {} doesn't match (run the tests with {}=1 to update it):\n{}",
+            path.display(),
+            UPDATE_VAR,
+            diff(&expected, actual)
+        ),
+    }
+}
+
+/// Differing lines, with their line numbers
+fn diff(expected: &str, actual: &str) -> String {
+    expected
+        .lines()
+        .zip_longest(actual.lines())
+        .enumerate()
+        .filter_map(|(idx, lines)| match lines {
+            EitherOrBoth::Both(e, a) if e == a => None,
+            EitherOrBoth::Both(e, a) => Some(format!("{:>4} - {}\n{:>4} + {}", idx + 1, e, "", a)),
+            EitherOrBoth::Left(e) => Some(format!("{:>4} - {}", idx + 1, e)),
+            EitherOrBoth::Right(a) => Some(format!("{:>4} + {}", idx + 1, a)),
+        })
+        .join("\n")
+}
+
+/// In the pretty `Debug` form, a block starts with a line ending with `{`, `[` or `(`, has one
+/// entry per line (or several lines, indented by 4 more spaces, for nested blocks), and ends with
+/// a line at the same indentation as its start.
+fn normalise(lines: &[&str]) -> Vec<String> {
+    let mut normalised: Vec<String> = Vec::with_capacity(lines.len());
+    let mut i = 0;
+    while i < lines.len() {
+        normalised.push(lines[i].to_owned());
+        // The end of a block can start another one, e.g. `}: Value {` for a struct key in a map
+        while let Some(bracket) = opening(lines[i]) {
+            let indent = indentation(lines[i]);
+            let end = (i + 1..lines.len())
+                .find(|&j| indentation(lines[j]) <= indent)
+                .expect("unbalanced Debug output");
+            let mut entries = entries(&lines[i + 1..end], indent + 4)
+                .iter()
+                .map(|e| normalise(e))
+                .collect_vec();
+            let unordered = bracket == '{' && is_unordered(lines[i]);
+            if unordered {
+                entries.sort_by(|a, b| natural_cmp(&a.join("\n"), &b.join("\n")));
+            }
+
+            let single_line = entries
+                .iter()
+                .map(|e| match e.as_slice() {
+                    [value] if !value.contains(['[', '{']) => {
+                        let value = value.trim_start();
+                        Some(value.strip_suffix(',').unwrap_or(value))
+                    }
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|values| values.join(", "))
+                // Rows of grids can be long, but long maps and sets are easier to read (and to
+                // diff) with one entry per line
+                .filter(|values| {
+                    !entries.is_empty()
+                        && (bracket != '{' || unordered && values.len() <= MAX_MAP_WIDTH)
+                });
+            match single_line {
+                Some(values) => {
+                    let last = normalised.last_mut().unwrap();
+                    last.push_str(&values);
+                    last.push_str(lines[end].trim_start());
+                }
+                None => {
+                    normalised.extend(entries.into_iter().flatten());
+                    normalised.push(lines[end].to_owned());
+                }
+            }
+            i = end;
+        }
+        i += 1;
+    }
+    normalised
+}
+
+fn opening(line: &str) -> Option<char> {
+    line.chars().last().filter(|c| "{[(".contains(*c))
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Maps and sets have no type name before their `{`, unlike structs.
+fn is_unordered(opening_line: &str) -> bool {
+    let head = opening_line[..opening_line.len() - 1].trim();
+    head.is_empty() || head.ends_with(':')
+}
+
+/// Compare strings with the numbers they contain compared by value, e.g. "a9" < "a10"
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    fn chunks(s: &str) -> impl Iterator<Item = (bool, &str)> {
+        s.as_bytes()
+            .chunk_by(|x, y| x.is_ascii_digit() == y.is_ascii_digit())
+            .map(|chunk| {
+                let chunk = std::str::from_utf8(chunk).unwrap();
+                (chunk.as_bytes()[0].is_ascii_digit(), chunk)
+            })
+    }
+    for pair in chunks(a).zip_longest(chunks(b)) {
+        let ordering = match pair {
+            EitherOrBoth::Both((true, x), (true, y)) => {
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            EitherOrBoth::Both((_, x), (_, y)) => x.cmp(y),
+            EitherOrBoth::Left(_) => Ordering::Greater,
+            EitherOrBoth::Right(_) => Ordering::Less,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.cmp(b)
+}
+
+/// Split the lines of a block into its entries: each entry ends with a `,` at the block's
+/// indentation.
+fn entries<'a>(lines: &[&'a str], indent: usize) -> Vec<Vec<&'a str>> {
+    let mut entries = Vec::new();
+    let mut entry = Vec::new();
+    for &line in lines {
+        entry.push(line);
+        if indentation(line) == indent && line.ends_with(',') {
+            entries.push(std::mem::take(&mut entry));
+        }
+    }
+    if !entry.is_empty() {
+        entries.push(entry);
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    #[allow(dead_code)] // only read through `Debug`
+    #[derive(Debug, Hash, PartialEq, Eq)]
+    struct Point {
+        x: i64,
+        y: i64,
+    }
+
+    #[allow(dead_code)] // only read through `Debug`
+    #[derive(Debug)]
+    struct Grid {
+        name: &'static str,
+        tiles: Vec<Vec<bool>>,
+        labels: HashMap<String, (usize, Option<usize>)>,
+    }
+
+    #[test]
+    fn test_to_snapshot() {
+        let grid = Grid {
+            name: "a, (b)",
+            tiles: vec![vec![true, false], vec![]],
+            labels: [9, 10, 2]
+                .into_iter()
+                .map(|i| (format!("l{}", i), (i, Some(2 * i))))
+                .collect(),
+        };
+        assert_eq!(
+            to_snapshot(&grid),
+            "Grid {
+    name: \"a, (b)\",
+    tiles: [
+        [true, false],
+        [],
+    ],
+    labels: {\"l2\": (2, Some(4)), \"l9\": (9, Some(18)), \"l10\": (10, Some(20))},
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_to_snapshot_is_stable() {
+        let points: HashSet<Point> = (0..50).map(|i| Point { x: i % 7, y: i }).collect();
+        let map: HashMap<Point, Vec<u8>> = (0..50)
+            .map(|i| (Point { x: i, y: -i }, vec![1, 2]))
+            .collect();
+        let snapshot = to_snapshot(&(&points, &map));
+        for _ in 0..5 {
+            let points: HashSet<&Point> = points.iter().collect();
+            let map: HashMap<&Point, &Vec<u8>> = map.iter().collect();
+            assert_eq!(to_snapshot(&(&points, &map)), snapshot);
+        }
+        // Struct keys span several lines, up to the value
+        assert!(snapshot.contains(
+            "        Point {
+            x: 0,
+            y: 0,
+        }: [1, 2],
+"
+        ));
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut values = vec!["b", "a10", "a9", "a09b", "a9a", "", "10", "9"];
+        values.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(values, vec!["", "9", "10", "a9", "a9a", "a09b", "a10", "b"]);
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            diff("a\nb\nc", "a\nB\nc\nd"),
+            "   2 - b\n     + B\n   4 + d"
+        );
+    }
+}
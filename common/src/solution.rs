@@ -0,0 +1,16 @@
+use std::fmt::Display;
+
+/// Implemented by each day's solution, for the tools which run all the days (see the `runner`
+/// crate): they read the answers (and markers) printed by `print_answer`.
+pub trait Solution {
+    /// Parts which only return a placeholder answer (e.g. a template which hasn't been solved
+    /// yet), so that they aren't reported as solved.
+    const STUBS: &'static [u8] = &[];
+
+    fn print_answer(part: u8, answer: impl Display) {
+        println!("Part {} result: {}", part, answer);
+        if Self::STUBS.contains(&part) {
+            println!("Part {} is a stub", part);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+use std::io;
+use std::io::BufRead;
+
+pub const DIGITS: [(&str, u32); 10] = [
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+pub const SPELLED_DIGITS: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+const ROOT: usize = 0;
+
+/// Aho–Corasick automaton finding all the occurrences of a vocabulary of digits, including
+/// overlapping ones (e.g. both 8 and 2 in `eightwo`), in a single pass over the bytes.
+pub struct DigitMatcher {
+    /// Complete transition table (failure links are already resolved)
+    transitions: Vec<[usize; 256]>,
+    /// (length, value) of all the words which end in each state
+    outputs: Vec<Vec<(usize, u32)>>,
+}
+
+impl DigitMatcher {
+    pub fn new<'a>(vocabulary: impl IntoIterator<Item = (&'a str, u32)>) -> DigitMatcher {
+        // Trie of all the words (0 meaning "no transition yet", since the root is never a target)
+        let mut transitions = vec![[ROOT; 256]];
+        let mut outputs = vec![Vec::new()];
+        for (word, value) in vocabulary {
+            let mut state = ROOT;
+            for &b in word.as_bytes() {
+                if transitions[state][b as usize] == ROOT {
+                    transitions.push([ROOT; 256]);
+                    outputs.push(Vec::new());
+                    transitions[state][b as usize] = transitions.len() - 1;
+                }
+                state = transitions[state][b as usize];
+            }
+            outputs[state].push((word.len(), value));
+        }
+
+        // Breadth-first, so that the failure link of a state is resolved before its children
+        let mut failures = vec![ROOT; transitions.len()];
+        let mut queue: VecDeque<usize> = transitions[ROOT]
+            .iter()
+            .copied()
+            .filter(|&s| s != ROOT)
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            let failure_outputs = outputs[failures[state]].clone();
+            outputs[state].extend(failure_outputs);
+            let failure_transitions = transitions[failures[state]];
+            for (child, failure_next) in transitions[state].iter_mut().zip(failure_transitions) {
+                if *child == ROOT {
+                    *child = failure_next;
+                } else {
+                    failures[*child] = failure_next;
+                    queue.push_back(*child);
+                }
+            }
+        }
+
+        DigitMatcher { transitions, outputs }
+    }
+
+    /// First and last digits (by starting position) of the calibration line
+    #[cfg(test)]
+    fn first_and_last(&self, line: &[u8]) -> Option<(u32, u32)> {
+        let mut calibration = CalibrationLine::default();
+        let mut state = ROOT;
+        for (pos, &b) in line.iter().enumerate() {
+            state = self.transitions[state][b as usize];
+            calibration.add_matches(pos, &self.outputs[state]);
+        }
+        calibration
+            .first
+            .zip(calibration.last)
+            .map(|((_, f), (_, l))| (f, l))
+    }
+
+    /// Sum of the calibration values of all the lines, reading the document in a streaming
+    /// fashion (even a single line doesn't need to fit in memory).
+    pub fn calibration_sum(&self, mut reader: impl BufRead) -> io::Result<u64> {
+        let mut sum = 0;
+        let mut calibration = CalibrationLine::default();
+        let mut state = ROOT;
+        let mut pos = 0;
+        loop {
+            let buf = reader.fill_buf()?;
+            if buf.is_empty() {
+                break;
+            }
+            for &b in buf {
+                if b == b'\n' {
+                    sum += calibration.value();
+                    calibration = CalibrationLine::default();
+                    state = ROOT;
+                    pos = 0;
+                } else {
+                    state = self.transitions[state][b as usize];
+                    calibration.add_matches(pos, &self.outputs[state]);
+                    pos += 1;
+                }
+            }
+            let len = buf.len();
+            reader.consume(len);
+        }
+        Ok(sum + calibration.value())
+    }
+}
+
+/// (start position, value) of the first and last digits found so far on a line
+#[derive(Default)]
+struct CalibrationLine {
+    first: Option<(usize, u32)>,
+    last: Option<(usize, u32)>,
+}
+
+impl CalibrationLine {
+    fn add_matches(&mut self, end_pos: usize, matches: &[(usize, u32)]) {
+        for &(len, value) in matches {
+            let start = end_pos + 1 - len;
+            if self.first.is_none_or(|(s, _)| start < s) {
+                self.first = Some((start, value));
+            }
+            if self.last.is_none_or(|(s, _)| start > s) {
+                self.last = Some((start, value));
+            }
+        }
+    }
+
+    fn value(&self) -> u64 {
+        match (self.first, self.last) {
+            (Some((_, first)), Some((_, last))) => (first * 10 + last) as u64,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlaps() {
+        let matcher = DigitMatcher::new(DIGITS.into_iter().chain(SPELLED_DIGITS));
+        assert_eq!(matcher.first_and_last(b"eightwo"), Some((8, 2)));
+        assert_eq!(matcher.first_and_last(b"xtwone3four"), Some((2, 4)));
+        assert_eq!(matcher.first_and_last(b"oneight"), Some((1, 8)));
+        assert_eq!(matcher.first_and_last(b"sevenine"), Some((7, 9)));
+        assert_eq!(matcher.first_and_last(b"twone"), Some((2, 1)));
+        assert_eq!(matcher.first_and_last(b"ninine"), Some((9, 9)));
+        assert_eq!(matcher.first_and_last(b"abc"), None);
+    }
+
+    #[test]
+    fn test_nested_words() {
+        // "bc" is found first, but "abcd" starts earlier
+        let matcher = DigitMatcher::new([("abcd", 1), ("bc", 2), ("d", 3)]);
+        assert_eq!(matcher.first_and_last(b"xabcdx"), Some((1, 3)));
+        assert_eq!(matcher.first_and_last(b"xbcx"), Some((2, 2)));
+    }
+
+    #[test]
+    fn test_custom_vocabulary() {
+        let french = [
+            ("zero", 0),
+            ("un", 1),
+            ("deux", 2),
+            ("trois", 3),
+            ("quatre", 4),
+            ("cinq", 5),
+            ("six", 6),
+            ("sept", 7),
+            ("huit", 8),
+            ("neuf", 9),
+        ];
+        let matcher = DigitMatcher::new(DIGITS.into_iter().chain(french));
+        assert_eq!(matcher.first_and_last(b"zeroxdeuxhuit"), Some((0, 8)));
+        assert_eq!(matcher.first_and_last(b"septrois"), Some((7, 3)));
+        assert_eq!(
+            matcher
+                .calibration_sum("un2\nzerosept\n\nneuf".as_bytes())
+                .unwrap(),
+            12 + 7 + 99
+        );
+    }
+
+    #[test]
+    fn test_streaming() {
+        // A single huge line, read through a tiny buffer
+        let line = format!("two{}seven", "x".repeat(100_000));
+        let document = format!("{}\n{}", line, line);
+        let reader = io::BufReader::with_capacity(7, document.as_bytes());
+        let matcher = DigitMatcher::new(DIGITS.into_iter().chain(SPELLED_DIGITS));
+        assert_eq!(matcher.calibration_sum(reader).unwrap(), 27 * 2);
+    }
+}
@@ -1,148 +1,159 @@
+mod render;
+
+use crate::render::{render, RenderMode};
 use common::itertools::Itertools;
-use std::collections::HashMap;
+use common::Solution;
 
 static INPUT: &str = include_str!("input");
 
+struct Day10;
+
+impl Solution for Day10 {}
+
 fn main() {
-    let pipes_map = parse_input(INPUT);
+    let pipes_map = parse_input(INPUT).unwrap();
 
     let res1 = part_1(&pipes_map);
-    println!("Part 1 result: {}", res1);
+    Day10::print_answer(1, res1);
 
     let res2 = part_2(&pipes_map);
-    println!("Part 2 result: {}", res2);
+    Day10::print_answer(2, res2);
+
+    // e.g. `cargo run -p day_10 -- --render`
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--render" => print!("{}", render(&pipes_map, RenderMode::Ansi)),
+            "--render-plain" => print!("{}", render(&pipes_map, RenderMode::Plain)),
+            _ => eprintln!("Unknown argument {}", arg),
+        }
+    }
 }
 
 fn part_1(pipes_map: &PipesMap) -> usize {
-    trace_loop(pipes_map).len() / 2
+    pipes_map.pipes_loop.len() / 2
 }
 
 fn part_2(pipes_map: &PipesMap) -> usize {
-    let pipes_loop = trace_loop(pipes_map);
-
-    let mut inside_points_count = 0;
-    for y in 0..pipes_map.size {
-        for x in 0..pipes_map.size {
-            // Don't count points *on* the loop
-            if (x < pipes_map.size - 1) && !pipes_loop.contains_key(&Point { x, y }) {
-                // count intersections until a point to the right, just outside the map (which means it's also outside the loop)
-                let mut intersections_count = 0;
-                let mut prev = TileType::Ground;
-                for x2 in (x + 1)..pipes_map.size {
-                    if let Some(&tile) = pipes_loop.get(&Point { x: x2, y }) {
-                        if tile == TileType::UpDown {
-                            intersections_count += 1;
-                        } else if tile == TileType::DownRight || tile == TileType::UpRight {
-                            prev = tile;
-                        } else if (tile == TileType::DownLeft && prev == TileType::UpRight)
-                            || (tile == TileType::UpLeft && prev == TileType::DownRight)
-                        {
-                            intersections_count += 1;
-                        }
-                    }
-                }
+    let pipes_loop = &pipes_map.pipes_loop;
+    let inside_points_count = count_enclosed_scanline(pipes_map, pipes_loop);
+    debug_assert_eq!(inside_points_count, count_enclosed_shoelace(pipes_loop));
+    inside_points_count
+}
+
+fn count_enclosed_scanline(pipes_map: &PipesMap, pipes_loop: &[Point]) -> usize {
+    classify_tiles(pipes_map, pipes_loop)
+        .iter()
+        .flatten()
+        .filter(|&&c| c == TileClass::Inside)
+        .count()
+}
 
-                // an odd number of intersections means the point is inside the loop
-                if intersections_count % 2 != 0 {
-                    inside_points_count += 1;
+/// Classify the tiles with a single pass over each row: going from left to right, we're inside
+/// the loop after crossing it an odd number of times. Only the loop tiles which connect upwards
+/// count as crossings, so that e.g. `L7` counts once and `LJ` twice.
+fn classify_tiles(pipes_map: &PipesMap, pipes_loop: &[Point]) -> Vec<Vec<TileClass>> {
+    let mut classes = vec![vec![TileClass::Outside; pipes_map.width]; pipes_map.height];
+    for &Point { x, y } in pipes_loop {
+        classes[y][x] = TileClass::Loop;
+    }
+
+    for (y, row) in classes.iter_mut().enumerate() {
+        let mut inside = false;
+        for (x, class) in row.iter_mut().enumerate() {
+            if *class == TileClass::Loop {
+                if pipes_map.tiles[y][x].connects(Direction::Up) {
+                    inside = !inside;
                 }
+            } else if inside {
+                *class = TileClass::Inside;
             }
         }
     }
-    inside_points_count
+    classes
 }
 
-fn trace_loop(pipes_map: &PipesMap) -> HashMap<Point, TileType> {
-    let mut pipes_loop = HashMap::new();
+/// Count the tiles enclosed by the loop with the shoelace formula (area of the polygon formed by
+/// the centers of the loop tiles) and Pick's theorem (`area = inside + boundary / 2 - 1`)
+fn count_enclosed_shoelace(pipes_loop: &[Point]) -> usize {
+    let double_area = pipes_loop
+        .iter()
+        .circular_tuple_windows()
+        .map(|(p1, p2)| (p1.x * p2.y) as i64 - (p2.x * p1.y) as i64)
+        .sum::<i64>()
+        .unsigned_abs() as usize;
+    (double_area + 2 - pipes_loop.len()) / 2
+}
+
+/// All the points of the loop, in order, starting from `start`. `None` if following the pipes
+/// leads to a tile which doesn't connect back, or out of the map, before returning to `start`.
+fn trace_loop(pipes_map: &PipesMap) -> Option<Vec<Point>> {
+    let mut pipes_loop = vec![pipes_map.start];
 
     let mut prev_point = pipes_map.start;
-    pipes_loop.insert(prev_point, pipes_map.get_tile(&prev_point));
     let mut curr_point = pipes_map.get_connected_points(&pipes_map.start)[0];
-    pipes_loop.insert(curr_point, pipes_map.get_tile(&curr_point));
-
-    let mut size = pipes_loop.len();
-    loop {
-        let new_point = pipes_map
-            .get_connected_points(&curr_point)
-            .into_iter()
-            .find(|&p| p != prev_point)
-            .unwrap();
+    while curr_point != pipes_map.start {
+        pipes_loop.push(curr_point);
+        let connected_points = pipes_map.get_connected_points(&curr_point);
+        if !connected_points.contains(&prev_point) {
+            return None;
+        }
+        let new_point = connected_points.into_iter().find(|&p| p != prev_point)?;
         prev_point = curr_point;
         curr_point = new_point;
-        pipes_loop.insert(curr_point, pipes_map.get_tile(&curr_point));
-        if pipes_loop.len() == size {
-            break;
-        } else {
-            size += 1;
-        }
     }
 
-    pipes_loop
+    Some(pipes_loop)
 }
 
-fn parse_input(input: &str) -> PipesMap {
-    let size = input.lines().next().unwrap().len();
-    let mut start = Point { x: 0, y: 0 };
-    let mut tiles = input
+fn parse_input(input: &str) -> Result<PipesMap, ParseError> {
+    let mut start = None;
+    let tiles = input
         .lines()
         .enumerate()
         .map(|(y, line)| {
             line.chars()
                 .enumerate()
                 .map(|(x, c)| match c {
-                    '|' => TileType::UpDown,
-                    '-' => TileType::LeftRight,
-                    'L' => TileType::UpRight,
-                    'J' => TileType::UpLeft,
-                    '7' => TileType::DownLeft,
-                    'F' => TileType::DownRight,
-                    '.' => TileType::Ground,
                     'S' => {
-                        start = Point { x, y };
-                        TileType::Ground // temporary
+                        start = Some(Point { x, y });
+                        Ok(TileType::Ground) // temporary
                     }
-                    _ => panic!("Invalid char {}", c),
+                    _ => TileType::from_char(c).ok_or(ParseError::InvalidTile(c)),
                 })
-                .collect_vec()
+                .collect::<Result<Vec<_>, _>>()
         })
-        .collect_vec();
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let Point { x, y } = start;
-    let connected_up = y > 0 && {
-        let t = tiles[y - 1][x];
-        t == TileType::UpDown || t == TileType::DownLeft || t == TileType::DownRight
-    };
-    let connected_right = x < size - 1 && {
-        let t = tiles[y][x + 1];
-        t == TileType::LeftRight || t == TileType::UpLeft || t == TileType::DownLeft
-    };
-    let connected_down = y < size - 1 && {
-        let t = tiles[y + 1][x];
-        t == TileType::UpDown || t == TileType::UpLeft || t == TileType::UpRight
-    };
-    let connected_left = x > 0 && {
-        let t = tiles[y][x - 1];
-        t == TileType::LeftRight || t == TileType::UpRight || t == TileType::DownRight
-    };
+    let height = tiles.len();
+    let width = tiles.first().map_or(0, |row| row.len());
+    if let Some(y) = tiles.iter().position(|row| row.len() != width) {
+        return Err(ParseError::InvalidRowLength { y, length: tiles[y].len(), width });
+    }
+
+    let start = start.ok_or(ParseError::MissingStart)?;
+    let mut pipes_map = PipesMap { width, height, tiles, start, pipes_loop: Vec::new() };
 
-    let start_type = if connected_left && connected_right {
-        TileType::LeftRight
-    } else if connected_up && connected_down {
-        TileType::UpDown
-    } else if connected_up && connected_right {
-        TileType::UpRight
-    } else if connected_up && connected_right {
-        TileType::UpLeft
-    } else if connected_down && connected_left {
-        TileType::DownLeft
-    } else if connected_down && connected_right {
-        TileType::DownRight
-    } else {
-        panic!("start point {:?} is not connected", start);
+    // The start tile connects to the neighbours which connect back to it
+    let connected_directions = Direction::ALL
+        .into_iter()
+        .filter(|&direction| {
+            pipes_map
+                .neighbour(&start, direction)
+                .is_some_and(|n| pipes_map.get_tile(&n).connects(direction.opposite()))
+        })
+        .collect_vec();
+    let start_type = match connected_directions[..] {
+        [d1, d2] => TileType::PIPES
+            .into_iter()
+            .find(|t| t.connects(d1) && t.connects(d2))
+            .unwrap(),
+        [] | [_] => return Err(ParseError::DisconnectedStart(start)),
+        _ => return Err(ParseError::AmbiguousStart(start, connected_directions)),
     };
-    tiles[start.y][start.x] = start_type;
+    pipes_map.tiles[start.y][start.x] = start_type;
+    pipes_map.pipes_loop = trace_loop(&pipes_map).ok_or(ParseError::OpenLoop(start))?;
 
-    PipesMap { size, tiles, start }
+    Ok(pipes_map)
 }
 
 impl PipesMap {
@@ -150,76 +161,58 @@ impl PipesMap {
         self.tiles[coords.y][coords.x]
     }
 
-    fn get_connected_points(&self, coords: &Point) -> Vec<Point> {
-        let &Point { x, y } = coords;
-
-        let add_up = |v: &mut Vec<Point>| {
-            if y > 0 {
-                v.push(Point { x, y: y - 1 });
-            }
-        };
-        let add_right = |v: &mut Vec<Point>| {
-            if x < self.size - 1 {
-                v.push(Point { x: x + 1, y });
-            }
-        };
-        let add_down = |v: &mut Vec<Point>| {
-            if y < self.size - 1 {
-                v.push(Point { x, y: y + 1 });
-            }
-        };
-        let add_left = |v: &mut Vec<Point>| {
-            if x > 0 {
-                v.push(Point { x: x - 1, y });
-            }
-        };
-
-        match self.get_tile(coords) {
-            TileType::UpDown => {
-                let mut connected = Vec::new();
-                add_up(&mut connected);
-                add_down(&mut connected);
-                connected
-            }
-            TileType::LeftRight => {
-                let mut connected = Vec::new();
-                add_left(&mut connected);
-                add_right(&mut connected);
-                connected
-            }
-            TileType::UpRight => {
-                let mut connected = Vec::new();
-                add_up(&mut connected);
-                add_right(&mut connected);
-                connected
-            }
-            TileType::UpLeft => {
-                let mut connected = Vec::new();
-                add_up(&mut connected);
-                add_left(&mut connected);
-                connected
-            }
-            TileType::DownLeft => {
-                let mut connected = Vec::new();
-                add_down(&mut connected);
-                add_left(&mut connected);
-                connected
-            }
-            TileType::DownRight => {
-                let mut connected = Vec::new();
-                add_down(&mut connected);
-                add_right(&mut connected);
-                connected
-            }
-            TileType::Ground => Vec::new(),
+    /// `None` if the neighbour would be outside the map
+    fn neighbour(&self, &Point { x, y }: &Point, direction: Direction) -> Option<Point> {
+        match direction {
+            Direction::Up if y > 0 => Some(Point { x, y: y - 1 }),
+            Direction::Right if x + 1 < self.width => Some(Point { x: x + 1, y }),
+            Direction::Down if y + 1 < self.height => Some(Point { x, y: y + 1 }),
+            Direction::Left if x > 0 => Some(Point { x: x - 1, y }),
+            _ => None,
         }
     }
+
+    fn get_connected_points(&self, coords: &Point) -> Vec<Point> {
+        self.get_tile(coords)
+            .directions()
+            .iter()
+            .filter_map(|&direction| self.neighbour(coords, direction))
+            .collect()
+    }
 }
 
+#[derive(Debug)]
 struct PipesMap {
-    size: usize,
+    width: usize,
+    height: usize,
     tiles: Vec<Vec<TileType>>,
     start: Point,
+    /// All the points of the loop, in order, starting from `start`
+    pipes_loop: Vec<Point>,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum TileClass {
+    Loop,
+    Inside,
+    Outside,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum ParseError {
+    InvalidTile(char),
+    InvalidRowLength {
+        y: usize,
+        length: usize,
+        width: usize,
+    },
+    MissingStart,
+    /// The start is connected to less than 2 pipes
+    DisconnectedStart(Point),
+    /// The start is connected to more than 2 pipes, so its own pipe type can't be inferred
+    AmbiguousStart(Point, Vec<Direction>),
+    /// Following the pipes from the start doesn't lead back to it
+    OpenLoop(Point),
 }
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
@@ -228,6 +221,72 @@ struct Point {
     y: usize,
 }
 
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+}
+
+impl TileType {
+    const PIPES: [TileType; 6] = [
+        TileType::UpDown,
+        TileType::LeftRight,
+        TileType::UpRight,
+        TileType::UpLeft,
+        TileType::DownLeft,
+        TileType::DownRight,
+    ];
+
+    fn from_char(c: char) -> Option<TileType> {
+        match c {
+            '|' => Some(TileType::UpDown),
+            '-' => Some(TileType::LeftRight),
+            'L' => Some(TileType::UpRight),
+            'J' => Some(TileType::UpLeft),
+            '7' => Some(TileType::DownLeft),
+            'F' => Some(TileType::DownRight),
+            '.' => Some(TileType::Ground),
+            _ => None,
+        }
+    }
+
+    fn directions(self) -> &'static [Direction] {
+        match self {
+            TileType::UpDown => &[Direction::Up, Direction::Down],
+            TileType::LeftRight => &[Direction::Left, Direction::Right],
+            TileType::UpRight => &[Direction::Up, Direction::Right],
+            TileType::UpLeft => &[Direction::Up, Direction::Left],
+            TileType::DownLeft => &[Direction::Down, Direction::Left],
+            TileType::DownRight => &[Direction::Down, Direction::Right],
+            TileType::Ground => &[],
+        }
+    }
+
+    fn connects(self, direction: Direction) -> bool {
+        self.directions().contains(&direction)
+    }
+}
+
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 enum TileType {
     UpDown,
@@ -243,6 +302,88 @@ enum TileType {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_start_type() {
+        let start_type = |input: &str| {
+            let pipes_map = parse_input(input).unwrap();
+            pipes_map.get_tile(&pipes_map.start)
+        };
+        assert_eq!(start_type("F-7\n|.|\nLSJ"), TileType::LeftRight);
+        assert_eq!(start_type("F7\nS|\nLJ"), TileType::UpDown);
+        assert_eq!(start_type("F7\nSJ"), TileType::UpRight);
+        assert_eq!(start_type("F7\nLS"), TileType::UpLeft);
+        assert_eq!(start_type("FS\nLJ"), TileType::DownLeft);
+        assert_eq!(start_type("S7\nLJ"), TileType::DownRight);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_input(".|.\n-S-\n.|.").err(),
+            Some(ParseError::AmbiguousStart(
+                Point { x: 1, y: 1 },
+                Direction::ALL.to_vec()
+            ))
+        );
+        assert_eq!(
+            parse_input(".-.\n|S|\n.-.").err(),
+            Some(ParseError::DisconnectedStart(Point { x: 1, y: 1 }))
+        );
+        assert_eq!(
+            parse_input("S-").err(),
+            Some(ParseError::DisconnectedStart(Point { x: 0, y: 0 }))
+        );
+        assert_eq!(parse_input("F7\nLJ").err(), Some(ParseError::MissingStart));
+        assert_eq!(
+            parse_input("S7\nLX").err(),
+            Some(ParseError::InvalidTile('X'))
+        );
+        assert_eq!(
+            parse_input("S7\nLJ.").err(),
+            Some(ParseError::InvalidRowLength { y: 1, length: 3, width: 2 })
+        );
+        assert_eq!(
+            parse_input("S-7\n|.|\nL-.").err(),
+            Some(ParseError::OpenLoop(Point { x: 0, y: 0 }))
+        );
+        assert_eq!(
+            parse_input("S-7\n|.|\nL--").err(),
+            Some(ParseError::OpenLoop(Point { x: 0, y: 0 }))
+        );
+        assert_eq!(
+            parse_input("S-7\n|.|\nL-7").err(),
+            Some(ParseError::OpenLoop(Point { x: 0, y: 0 }))
+        );
+    }
+
+    #[test]
+    fn test_rectangular_map() {
+        let wide = parse_input(
+            "..........
+.S------7.
+.|F----7|.
+.||....||.
+.|L-7F-J|.
+.L--JL--J.",
+        )
+        .unwrap();
+        assert_eq!((wide.width, wide.height), (10, 6));
+        assert_eq!(part_1(&wide), 18);
+        assert_eq!(part_2(&wide), 0);
+
+        let tall = parse_input(
+            "S-7
+|.|
+|.|
+|.|
+L-J",
+        )
+        .unwrap();
+        assert_eq!((tall.width, tall.height), (3, 5));
+        assert_eq!(part_1(&tall), 6);
+        assert_eq!(part_2(&tall), 3);
+    }
+
     #[test]
     fn test_part_1() {
         let test_input_1 = parse_input(
@@ -251,7 +392,8 @@ mod tests {
 .|.|.
 .L-J.
 .....",
-        );
+        )
+        .unwrap();
         assert_eq!(part_1(&test_input_1), 4);
 
         let test_input_2 = parse_input(
@@ -260,7 +402,8 @@ mod tests {
 SJ.L7
 |F--J
 LJ...",
-        );
+        )
+        .unwrap();
         assert_eq!(part_1(&test_input_2), 8);
     }
 
@@ -278,7 +421,8 @@ LJ...",
 ...........
 ...........
 ...........",
-        );
+        )
+        .unwrap();
         assert_eq!(part_2(&test_input_1), 4);
 
         let test_input_2 = parse_input(
@@ -293,7 +437,8 @@ LJ...",
 ..........
 ..........
 ..........",
-        );
+        )
+        .unwrap();
         assert_eq!(part_2(&test_input_2), 4);
 
         let test_input_3 = parse_input(
@@ -317,7 +462,8 @@ L--J.L7...LJS7F-7L7.
 ....................
 ....................
 ....................",
-        );
+        )
+        .unwrap();
         assert_eq!(part_2(&test_input_3), 8);
 
         let test_input_4 = parse_input(
@@ -341,7 +487,67 @@ L7JLJL-JLJLJL--JLJ.L
 ....................
 ....................
 ....................",
-        );
+        )
+        .unwrap();
         assert_eq!(part_2(&test_input_4), 10);
+
+        // Both methods must agree
+        for pipes_map in [test_input_1, test_input_2, test_input_3, test_input_4] {
+            let pipes_loop = &pipes_map.pipes_loop;
+            assert_eq!(
+                count_enclosed_shoelace(pipes_loop),
+                count_enclosed_scanline(&pipes_map, pipes_loop)
+            );
+        }
+    }
+
+    #[test]
+    fn test_trace_loop() {
+        let pipes_map = parse_input(
+            ".....
+.S-7.
+.|.|.
+.L-J.
+.....",
+        )
+        .unwrap();
+        let loop_points = pipes_map
+            .pipes_loop
+            .iter()
+            .map(|&Point { x, y }| (x, y))
+            .collect_vec();
+        assert_eq!(
+            loop_points,
+            vec![
+                (1, 1),
+                (1, 2),
+                (1, 3),
+                (2, 3),
+                (3, 3),
+                (3, 2),
+                (3, 1),
+                (2, 1)
+            ]
+        );
+        assert_eq!(count_enclosed_shoelace(&pipes_map.pipes_loop), 1);
+    }
+
+    #[test]
+    fn test_generated_mazes() {
+        let mut rng = common::gen::rng(10);
+        for (width, height) in [(1, 1), (3, 2), (10, 10), (40, 15)] {
+            let maze = common::gen::pipes::pipe_maze(&mut rng, width, height, 0.6);
+            let pipes_map = parse_input(&maze.input).unwrap();
+            assert_eq!(part_1(&pipes_map), maze.farthest, "{}", maze.input);
+            assert_eq!(part_2(&pipes_map), maze.enclosed, "{}", maze.input);
+        }
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!(
+            "test_input",
+            parse_input("..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...").unwrap()
+        );
     }
 }
@@ -0,0 +1,140 @@
+use crate::{classify_tiles, PipesMap, Point, TileClass, TileType};
+
+const RESET: &str = "\x1b[0m";
+const BOLD_GREEN: &str = "\x1b[1;32m";
+const BOLD_RED: &str = "\x1b[1;31m";
+const GREY: &str = "\x1b[90m";
+const YELLOW_BACKGROUND: &str = "\x1b[43m";
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum RenderMode {
+    /// No escape codes: the start is `S`, the farthest point from the start is `●`, and the
+    /// tiles inside the loop are `░`
+    Plain,
+    /// The start and the farthest point are highlighted, junk pipes are greyed out, and the
+    /// tiles inside the loop get a coloured background
+    Ansi,
+}
+
+/// Draw the main loop with heavy box-drawing characters, and the junk pipes with light ones.
+pub fn render(pipes_map: &PipesMap, mode: RenderMode) -> String {
+    let pipes_loop = &pipes_map.pipes_loop;
+    let farthest = pipes_loop[pipes_loop.len() / 2];
+    let classes = classify_tiles(pipes_map, pipes_loop);
+
+    let mut rendered = String::new();
+    for (y, row) in pipes_map.tiles.iter().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let point = Point { x, y };
+            let class = classes[y][x];
+            let glyph = match class {
+                TileClass::Loop => heavy_glyph(tile),
+                TileClass::Inside | TileClass::Outside => light_glyph(tile),
+            };
+            match mode {
+                RenderMode::Plain => rendered.push(match class {
+                    _ if point == pipes_map.start => 'S',
+                    _ if point == farthest => '●',
+                    TileClass::Inside => '░',
+                    TileClass::Loop | TileClass::Outside => glyph,
+                }),
+                RenderMode::Ansi => {
+                    let style = match class {
+                        _ if point == pipes_map.start => BOLD_GREEN,
+                        _ if point == farthest => BOLD_RED,
+                        TileClass::Loop => "",
+                        TileClass::Inside => YELLOW_BACKGROUND,
+                        TileClass::Outside => GREY,
+                    };
+                    if style.is_empty() {
+                        rendered.push(glyph);
+                    } else {
+                        rendered.push_str(&format!("{}{}{}", style, glyph, RESET));
+                    }
+                }
+            }
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
+fn heavy_glyph(tile: TileType) -> char {
+    match tile {
+        TileType::UpDown => '┃',
+        TileType::LeftRight => '━',
+        TileType::UpRight => '┗',
+        TileType::UpLeft => '┛',
+        TileType::DownLeft => '┓',
+        TileType::DownRight => '┏',
+        TileType::Ground => ' ',
+    }
+}
+
+fn light_glyph(tile: TileType) -> char {
+    match tile {
+        TileType::UpDown => '│',
+        TileType::LeftRight => '─',
+        TileType::UpRight => '└',
+        TileType::UpLeft => '┘',
+        TileType::DownLeft => '┐',
+        TileType::DownRight => '┌',
+        TileType::Ground => ' ',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    #[test]
+    fn test_render_plain() {
+        let pipes_map = parse_input(
+            "..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ.F7",
+        )
+        .unwrap();
+        assert_eq!(
+            render(&pipes_map, RenderMode::Plain),
+            "  ┏┓ \n ┏┛┃ \nS┛░┗●\n┃┏━━┛\n┗┛ ┌┐\n"
+        );
+        assert_eq!(crate::part_2(&pipes_map), 1);
+
+        let pipes_map = parse_input(
+            "S----7
+|F-7.|
+||.|.|
+|L-J.|
+L----J",
+        )
+        .unwrap();
+        assert_eq!(
+            render(&pipes_map, RenderMode::Plain),
+            "S━━━━┓
+┃░░░░┃
+┃░░░░┃
+┃░░░░┃
+┗━━━━●
+"
+        );
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let pipes_map = parse_input(
+            "S7.
+LJ-",
+        )
+        .unwrap();
+        assert_eq!(
+            render(&pipes_map, RenderMode::Ansi),
+            "\x1b[1;32m┏\x1b[0m┓\x1b[90m \x1b[0m
+┗\x1b[1;31m┛\x1b[0m\x1b[90m─\x1b[0m
+"
+        );
+    }
+}
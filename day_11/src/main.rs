@@ -1,38 +1,49 @@
 use common::itertools::Itertools;
+use common::Solution;
 
 static INPUT: &str = include_str!("input");
 
+struct Day11;
+
+impl Solution for Day11 {}
+
 fn main() {
     let universe = parse_input(INPUT);
 
     let res1 = part_1(&universe);
-    println!("Part 1 result: {}", res1);
+    Day11::print_answer(1, res1);
 
     let res2 = part_2(&universe);
-    println!("Part 2 result: {}", res2);
+    Day11::print_answer(2, res2);
 }
 
-fn part_1(universe: &Universe) -> usize {
+fn part_1(universe: &Universe) -> u128 {
     total_distances(universe, 2)
 }
 
-fn part_2(universe: &Universe) -> usize {
+fn part_2(universe: &Universe) -> u128 {
     total_distances(universe, 1_000_000)
 }
 
-fn total_distances(universe: &Universe, empty_space_multiplier: usize) -> usize {
-    let galaxies = universe.all_galaxies();
-
-    galaxies
-        .iter()
-        .enumerate()
-        .flat_map(|(idx, p1)| {
-            galaxies
-                .iter()
-                .skip(idx + 1)
-                .map(|p2| universe.distance(p1, p2, empty_space_multiplier))
-        })
-        .sum()
+fn total_distances(universe: &Universe, empty_space_multiplier: u64) -> u128 {
+    let (xs, ys): (Vec<_>, Vec<_>) = universe
+        .expanded_galaxies(empty_space_multiplier)
+        .into_iter()
+        .unzip();
+    sum_of_differences(xs) + sum_of_differences(ys)
+}
+
+/// Sum of `|a - b|` for all the pairs of values: once sorted, each value is larger than (or equal
+/// to) all the previous ones, so it only has to be compared to their sum.
+fn sum_of_differences(mut values: Vec<u128>) -> u128 {
+    values.sort_unstable();
+    let mut sum = 0;
+    let mut prev_values_sum = 0;
+    for (idx, &value) in values.iter().enumerate() {
+        sum += value * idx as u128 - prev_values_sum;
+        prev_values_sum += value;
+    }
+    sum
 }
 
 fn parse_input(input: &str) -> Universe {
@@ -57,7 +68,31 @@ fn parse_input(input: &str) -> Universe {
         .filter(|&x| spaces.iter().all(|row| row[x] == Space::Empty))
         .collect_vec();
 
-    Universe { height, width, spaces, empty_rows, empty_columns }
+    let empty_rows_before = count_before(height, &empty_rows);
+    let empty_columns_before = count_before(width, &empty_columns);
+
+    Universe {
+        height,
+        width,
+        spaces,
+        empty_rows,
+        empty_columns,
+        empty_rows_before,
+        empty_columns_before,
+    }
+}
+
+/// For each index in `0..len`, how many of the (sorted) `indices` are strictly lower
+fn count_before(len: usize, indices: &[usize]) -> Vec<usize> {
+    let mut counts = Vec::with_capacity(len);
+    let mut count = 0;
+    for idx in 0..len {
+        counts.push(count);
+        if indices.get(count) == Some(&idx) {
+            count += 1;
+        }
+    }
+    counts
 }
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
@@ -79,38 +114,36 @@ impl Universe {
         galaxies
     }
 
-    fn distance(&self, p1: &Point, p2: &Point, empty_space_multiplier: usize) -> usize {
-        let x_min = p1.x.min(p2.x);
-        let x_max = p1.x.max(p2.x);
-        let horiz_dist = (x_min..x_max).fold(0, |acc, x| {
-            if self.empty_columns.contains(&x) {
-                acc + empty_space_multiplier
-            } else {
-                acc + 1
-            }
-        });
-
-        let y_min = p1.y.min(p2.y);
-        let y_max = p1.y.max(p2.y);
-        let vert_dist = (y_min..y_max).fold(0, |acc, y| {
-            if self.empty_rows.contains(&y) {
-                acc + empty_space_multiplier
-            } else {
-                acc + 1
-            }
-        });
-
-        horiz_dist + vert_dist
+    /// Coordinates of all the galaxies, once each empty row or column is replaced by
+    /// `empty_space_multiplier` of them (possibly 0, which removes them)
+    fn expanded_galaxies(&self, empty_space_multiplier: u64) -> Vec<(u128, u128)> {
+        // The empty rows or columns before a galaxy are part of its coordinate
+        let expand = |coord: usize, empty_before: usize| {
+            (coord - empty_before) as u128 + empty_space_multiplier as u128 * empty_before as u128
+        };
+        self.all_galaxies()
+            .into_iter()
+            .map(|Point { x, y }| {
+                (
+                    expand(x, self.empty_columns_before[x]),
+                    expand(y, self.empty_rows_before[y]),
+                )
+            })
+            .collect()
     }
 }
 
-#[derive(PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 struct Universe {
     height: usize,
     width: usize,
     spaces: Vec<Vec<Space>>,
     empty_rows: Vec<usize>,
     empty_columns: Vec<usize>,
+    /// Number of empty rows above each row
+    empty_rows_before: Vec<usize>,
+    /// Number of empty columns left of each column
+    empty_columns_before: Vec<usize>,
 }
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
@@ -122,11 +155,9 @@ enum Space {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
-    #[test]
-    fn test() {
-        let g = parse_input(
-            "...#......
+    static TEST_INPUT: &str = "...#......
 .......#..
 #.........
 ..........
@@ -135,28 +166,103 @@ mod tests {
 .........#
 ..........
 .......#..
-#...#.....",
-        );
+#...#.....";
+
+    /// Straightforward computation, one pair of galaxies at a time
+    fn total_distances_naive(universe: &Universe, empty_space_multiplier: u64) -> u128 {
+        let distance = |p1: &Point, p2: &Point| -> u128 {
+            let horiz_dist: u128 = (p1.x.min(p2.x)..p1.x.max(p2.x))
+                .map(|x| {
+                    if universe.empty_columns.contains(&x) {
+                        empty_space_multiplier as u128
+                    } else {
+                        1
+                    }
+                })
+                .sum();
+            let vert_dist: u128 = (p1.y.min(p2.y)..p1.y.max(p2.y))
+                .map(|y| {
+                    if universe.empty_rows.contains(&y) {
+                        empty_space_multiplier as u128
+                    } else {
+                        1
+                    }
+                })
+                .sum();
+            horiz_dist + vert_dist
+        };
+
+        universe
+            .all_galaxies()
+            .iter()
+            .tuple_combinations()
+            .map(|(p1, p2)| distance(p1, p2))
+            .sum()
+    }
+
+    #[test]
+    fn test() {
+        let g = parse_input(TEST_INPUT);
         assert_eq!(g.empty_rows, vec![3, 7]);
         assert_eq!(g.empty_columns, vec![2, 5, 8]);
+        assert_eq!(g.empty_rows_before, vec![0, 0, 0, 0, 1, 1, 1, 1, 2, 2]);
+        assert_eq!(g.empty_columns_before, vec![0, 0, 0, 1, 1, 1, 2, 2, 2, 3]);
     }
 
     #[test]
     fn test_part_1() {
+        assert_eq!(part_1(&parse_input(TEST_INPUT)), 374);
+    }
+
+    #[test]
+    fn test_total_distances() {
+        let universe = parse_input(TEST_INPUT);
+        assert_eq!(total_distances(&universe, 10), 1030);
+        assert_eq!(total_distances(&universe, 100), 8410);
+        for multiplier in [0, 1, 2, 10, 1_000_000] {
+            assert_eq!(
+                total_distances(&universe, multiplier),
+                total_distances_naive(&universe, multiplier)
+            );
+        }
         assert_eq!(
-            part_1(&parse_input(
-                "...#......
-.......#..
-#.........
-..........
-......#...
-.#........
-.........#
-..........
-.......#..
-#...#....."
-            )),
-            374
+            total_distances(&universe, u64::MAX),
+            total_distances_naive(&universe, u64::MAX)
         );
     }
+
+    /// Rectangular universes of up to 12×12 spaces, with empty rows and columns likely
+    fn universe_input() -> impl Strategy<Value = String> {
+        (1..=12usize, 1..=12usize, 0.0..0.3f64).prop_flat_map(|(width, height, density)| {
+            prop::collection::vec(
+                prop::collection::vec(prop::bool::weighted(density), width),
+                height,
+            )
+            .prop_map(|rows| {
+                rows.iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|&g| if g { '#' } else { '.' })
+                            .collect::<String>()
+                    })
+                    .join("\n")
+            })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn total_distances_matches_naive(input in universe_input(), multiplier in 0..1_000_000u64) {
+            let universe = parse_input(&input);
+            prop_assert_eq!(
+                total_distances(&universe, multiplier),
+                total_distances_naive(&universe, multiplier)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_input(TEST_INPUT));
+    }
 }
@@ -0,0 +1,261 @@
+use crate::{count_next, Count, DamagedGroup, Record, StateTable};
+use common::rand::Rng;
+
+/// All the valid arrangements of a record, backed by the same dense DP table as
+/// `count_arrangements`: `prefixes[i]` counts the ways to reach each state before spring `i`, and
+/// `completions[i]` counts the ways to complete the record from each of those states.
+pub struct Arrangements<'a, T: Count> {
+    record: &'a Record,
+    states: StateTable,
+    prefixes: Vec<Vec<T>>,
+    completions: Vec<Vec<T>>,
+}
+
+impl<'a, T: Count> Arrangements<'a, T> {
+    pub fn new(record: &'a Record) -> Arrangements<'a, T> {
+        let Record { springs, damaged_groups } = record;
+        let states = StateTable::new(damaged_groups);
+
+        let mut prefixes = vec![vec![T::zero(); states.len()]; springs.len() + 1];
+        prefixes[0][states.index(DamagedGroup::START)] = T::one();
+        for (i, &spring) in springs.iter().enumerate() {
+            let (before, after) = prefixes.split_at_mut(i + 1);
+            count_next(&states, damaged_groups, spring, &before[i], &mut after[0]);
+        }
+
+        let mut completions = vec![vec![T::zero(); states.len()]; springs.len() + 1];
+        for group in states.states() {
+            if group.is_complete(damaged_groups) {
+                completions[springs.len()][states.index(group)] = T::one();
+            }
+        }
+        for i in (0..springs.len()).rev() {
+            for group in states.states() {
+                let mut count = T::zero();
+                for (next_group, _) in group.next(springs[i], damaged_groups) {
+                    count += &completions[i + 1][states.index(next_group)];
+                }
+                completions[i][states.index(group)] = count;
+            }
+        }
+
+        Arrangements { record, states, prefixes, completions }
+    }
+
+    pub fn count(&self) -> &T {
+        self.completions(0, DamagedGroup::START)
+    }
+
+    fn completions(&self, i: usize, group: DamagedGroup) -> &T {
+        &self.completions[i][self.states.index(group)]
+    }
+
+    /// Next states (and whether spring `i` is damaged) from which the record can still be completed
+    fn viable_next(&self, i: usize, group: DamagedGroup) -> Vec<(DamagedGroup, bool)> {
+        group
+            .next(self.record.springs[i], &self.record.damaged_groups)
+            .filter(|&(next_group, _)| !self.completions(i + 1, next_group).is_zero())
+            .collect()
+    }
+
+    /// Lazily enumerate the arrangements (`true` for a damaged spring). Each step of the
+    /// enumeration only explores states which lead to a valid arrangement, so it stays practical
+    /// even when there are far too many arrangements to list them all.
+    pub fn iter(&self) -> impl Iterator<Item = Vec<bool>> + '_ {
+        let mut stack = if self.count().is_zero() {
+            Vec::new()
+        } else {
+            vec![(DamagedGroup::START, Vec::new())]
+        };
+        std::iter::from_fn(move || {
+            while let Some((group, springs)) = stack.pop() {
+                let i = springs.len();
+                if i == self.record.springs.len() {
+                    return Some(springs);
+                }
+                // Damaged springs are pushed first, so that undamaged springs get explored first
+                for (next_group, damaged) in self.viable_next(i, group) {
+                    let mut next_springs = springs.clone();
+                    next_springs.push(damaged);
+                    stack.push((next_group, next_springs));
+                }
+            }
+            None
+        })
+    }
+
+    /// A uniformly random arrangement, or `None` if there isn't any.
+    pub fn sample(&self, rng: &mut impl Rng) -> Option<Vec<bool>> {
+        if self.count().is_zero() {
+            return None;
+        }
+        let mut group = DamagedGroup::START;
+        let mut springs = Vec::with_capacity(self.record.springs.len());
+        for i in 0..self.record.springs.len() {
+            // Each choice is weighted by the number of arrangements it leads to
+            let viable_next = self.viable_next(i, group);
+            let mut n = self.completions(i, group).random_below(rng);
+            let &(next_group, damaged) = viable_next
+                .iter()
+                .find(|&&(next_group, _)| {
+                    match n.checked_sub(self.completions(i + 1, next_group)) {
+                        Some(rest) => {
+                            n = rest;
+                            false
+                        }
+                        None => true,
+                    }
+                })
+                .unwrap();
+            group = next_group;
+            springs.push(damaged);
+        }
+        Some(springs)
+    }
+
+    /// Springs which are in the same state in every arrangement (`None` if the state can vary,
+    /// or if there is no arrangement at all).
+    pub fn forced_springs(&self) -> Vec<Option<bool>> {
+        let count = self.count();
+        (0..self.record.springs.len())
+            .map(|i| {
+                // Never more than `count`, which already fits in `T`
+                let mut damaged_count = T::zero();
+                for group in self.states.states() {
+                    let permutations = &self.prefixes[i][self.states.index(group)];
+                    for (next_group, damaged) in self.viable_next(i, group) {
+                        if damaged {
+                            let through = permutations
+                                .checked_mul(self.completions(i + 1, next_group))
+                                .expect("more arrangements through a spring than in total");
+                            damaged_count += &through;
+                        }
+                    }
+                }
+                match damaged_count {
+                    _ if count.is_zero() => None,
+                    c if c.is_zero() => Some(false),
+                    c if &c == count => Some(true),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{count_arrangements, parse_input, unfold};
+    use common::itertools::Itertools;
+    use common::num_bigint::BigUint;
+    use common::rand::rngs::StdRng;
+    use common::rand::SeedableRng;
+
+    fn parse_record(line: &str) -> Record {
+        parse_input(line).pop().unwrap()
+    }
+
+    fn format_springs(springs: &[bool]) -> String {
+        crate::format_springs(&springs.iter().copied().map(Some).collect_vec())
+    }
+
+    #[test]
+    fn test_iter() {
+        let record = parse_record(".??..??...?##. 1,1,3");
+        let arrangements = Arrangements::<u128>::new(&record);
+        assert_eq!(*arrangements.count(), 4);
+        assert_eq!(
+            arrangements
+                .iter()
+                .map(|a| format_springs(&a))
+                .collect_vec(),
+            vec![
+                "..#...#...###.",
+                "..#..#....###.",
+                ".#....#...###.",
+                ".#...#....###.",
+            ]
+        );
+
+        let record = parse_record("?###???????? 3,2,1");
+        assert_eq!(Arrangements::<u128>::new(&record).iter().count(), 10);
+
+        let record = parse_record("#.# 2");
+        assert_eq!(Arrangements::<u128>::new(&record).iter().next(), None);
+    }
+
+    #[test]
+    fn test_iter_unfolded() {
+        let record = unfold(&parse_record("?###???????? 3,2,1"), 5, None);
+        let arrangements = Arrangements::<u128>::new(&record);
+        assert_eq!(*arrangements.count(), 506250);
+        let first = arrangements.iter().take(1000).collect_vec();
+        assert_eq!(first.len(), 1000);
+        assert!(first.iter().all_unique());
+    }
+
+    #[test]
+    fn test_large_unfold() {
+        let record = unfold(&parse_record("?###???????? 3,2,1"), 50, None);
+        let arrangements = Arrangements::<BigUint>::new(&record);
+        assert_eq!(
+            *arrangements.count(),
+            count_arrangements::<BigUint>(&record)
+        );
+        assert!(*arrangements.count() > BigUint::from(u128::MAX));
+
+        let mut rng = StdRng::seed_from_u64(12);
+        for springs in [arrangements.iter().next(), arrangements.sample(&mut rng)] {
+            let springs = springs.unwrap();
+            let groups = springs
+                .split(|&damaged| !damaged)
+                .map(|group| group.len())
+                .filter(|&len| len > 0)
+                .collect_vec();
+            assert_eq!(groups, record.damaged_groups);
+        }
+        let forced = arrangements.forced_springs();
+        assert_eq!(
+            forced[..5],
+            [Some(false), Some(true), Some(true), Some(true), Some(false)]
+        );
+    }
+
+    #[test]
+    fn test_sample() {
+        let record = parse_record(".??..??...?##. 1,1,3");
+        let arrangements = Arrangements::<u128>::new(&record);
+        let all = arrangements.iter().collect_vec();
+
+        let mut rng = StdRng::seed_from_u64(12);
+        let samples = (0..4000)
+            .map(|_| arrangements.sample(&mut rng).unwrap())
+            .counts();
+        assert_eq!(samples.len(), all.len());
+        for a in all {
+            assert!((800..1200).contains(&samples[&a]));
+        }
+
+        let record = parse_record("#.# 2");
+        assert_eq!(Arrangements::<u128>::new(&record).sample(&mut rng), None);
+    }
+
+    #[test]
+    fn test_forced_springs() {
+        let record = parse_record("?###???????? 3,2,1");
+        let forced = Arrangements::<u128>::new(&record).forced_springs();
+        let expected = [Some(false), Some(true), Some(true), Some(true), Some(false)]
+            .into_iter()
+            .chain([None; 7])
+            .collect_vec();
+        assert_eq!(forced, expected);
+
+        let record = parse_record("???.### 1,1,3");
+        let forced = Arrangements::<u128>::new(&record).forced_springs();
+        assert_eq!(
+            format_springs(&forced.into_iter().flatten().collect_vec()),
+            "#.#.###"
+        );
+    }
+}
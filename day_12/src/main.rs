@@ -1,102 +1,163 @@
+mod arrangements;
+
+use crate::arrangements::Arrangements;
+
 use common::itertools::Itertools;
-use common::maplit::hashmap;
+use common::num_bigint::{BigUint, RandBigInt};
+use common::num_traits::{CheckedMul, CheckedSub, One, Zero};
+use common::rand::Rng;
 use common::time_execution;
-use std::collections::HashMap;
+use common::Solution;
+use std::ops::AddAssign;
 
 static INPUT: &str = include_str!("input");
 
+struct Day12;
+
+impl Solution for Day12 {}
+
 fn main() {
     let records = parse_input(INPUT);
 
     let res1 = time_execution("part 1", || part_1(&records));
-    println!("Part 1 result: {}", res1);
+    Day12::print_answer(1, res1);
 
     let res2 = time_execution("part 2", || part_2(&records));
-    println!("Part 2 result: {}", res2);
+    Day12::print_answer(2, res2);
+
+    // e.g. `cargo run -p day_12 -- --explore`
+    if std::env::args().any(|arg| arg == "--explore") {
+        explore(&records, &mut common::rand::thread_rng());
+    }
+
+    // e.g. `cargo run --release -p day_12 -- --unfolded`
+    if std::env::args().any(|arg| arg == "--unfolded") {
+        for factor in [20, 50] {
+            let res: BigUint = time_execution(&format!("unfolded x{}", factor), || {
+                count_unfolded_arrangements(&records, factor)
+            });
+            println!("Unfolded x{} result: {}", factor, res);
+        }
+    }
 }
 
-fn part_1(records: &[Record]) -> usize {
-    records
-        .iter()
-        .map(|record| count_arrangements(record))
-        .sum()
+/// Print the forced springs, the first arrangement and a random arrangement of each unfolded
+/// record (as in part 2)
+fn explore(records: &[Record], rng: &mut impl Rng) {
+    for record in records {
+        let unfolded = unfold(record, 5, None);
+        let arrangements = Arrangements::<u128>::new(&unfolded);
+        let known = |springs: Vec<bool>| springs.into_iter().map(Some).collect_vec();
+        println!(
+            "{} {}: {} arrangements",
+            format_springs(&unfolded.springs),
+            unfolded.damaged_groups.iter().join(","),
+            arrangements.count()
+        );
+        println!(
+            "  forced: {}",
+            format_springs(&arrangements.forced_springs())
+        );
+        if let Some(first) = arrangements.iter().next() {
+            println!("  first:  {}", format_springs(&known(first)));
+        }
+        if let Some(sample) = arrangements.sample(rng) {
+            println!("  random: {}", format_springs(&known(sample)));
+        }
+    }
 }
 
-fn part_2(records: &[Record]) -> usize {
-    records
+/// Same format as the input (`?` for an unknown spring)
+fn format_springs(springs: &[Option<bool>]) -> String {
+    springs
         .iter()
-        .map(|record| count_arrangements(&unfold(record)))
-        .sum()
+        .map(|spring| match spring {
+            Some(true) => '#',
+            Some(false) => '.',
+            None => '?',
+        })
+        .collect()
+}
+
+fn part_1(records: &[Record]) -> u128 {
+    records.iter().map(count_arrangements::<u128>).sum()
+}
+
+fn part_2(records: &[Record]) -> u128 {
+    count_unfolded_arrangements(records, 5)
+}
+
+fn count_unfolded_arrangements<T: Count>(records: &[Record], factor: usize) -> T {
+    records.iter().fold(T::zero(), |mut acc, record| {
+        acc += &count_arrangements(&unfold(record, factor, None));
+        acc
+    })
+}
+
+/// Type used to count the arrangements: large unfolding factors need big integers.
+trait Count: Clone + Ord + Zero + One + CheckedSub + CheckedMul + for<'a> AddAssign<&'a Self> {
+    /// Uniformly random count in `0..self`
+    fn random_below(&self, rng: &mut impl Rng) -> Self;
 }
 
-fn count_arrangements(Record { springs, damaged_groups }: &Record) -> usize {
+impl Count for u128 {
+    fn random_below(&self, rng: &mut impl Rng) -> Self {
+        rng.gen_range(0..*self)
+    }
+}
+
+impl Count for BigUint {
+    fn random_below(&self, rng: &mut impl Rng) -> Self {
+        rng.gen_biguint_below(self)
+    }
+}
+
+fn count_arrangements<T: Count>(Record { springs, damaged_groups }: &Record) -> T {
+    let states = StateTable::new(damaged_groups);
+
     // Before we start, we haven't yet counted any spring within the first group, and there is only one permutation possible for this
-    let init_permutations = hashmap! { DamagedGroup {idx: 0, amount: 0} => 1 };
-    springs
-        .iter()
-        .fold(init_permutations, |acc, &spring| {
-            let mut permutation_counts = HashMap::new();
-            let mut increment_permutations = |idx: usize, amount: usize, permutations: usize| {
-                let group_amount = DamagedGroup { idx, amount };
-                *permutation_counts.entry(group_amount).or_default() += permutations;
-            };
-
-            match spring {
-                None => {
-                    for (DamagedGroup { idx, amount }, permutations) in acc {
-                        // amount = 0: we can add a damaged spring (1) or an undamaged spring (2)
-                        // 0 < amount < group_size: we're in the middle of a group, we can only add a damaged spring (1)
-                        // amount = group_size: we've reached the end of a group, we can only add an undamaged spring and must increment the index (3)
-
-                        if idx < damaged_groups.len() && amount < damaged_groups[idx] {
-                            // 1
-                            increment_permutations(idx, amount + 1, permutations);
-                        }
-
-                        if amount == 0 {
-                            // 2
-                            increment_permutations(idx, 0, permutations);
-                        } else if amount == damaged_groups[idx] {
-                            // 3
-                            increment_permutations(idx + 1, 0, permutations);
-                        }
-                    }
-                }
-                Some(damaged_spring) => {
-                    for (DamagedGroup { idx, amount }, permutations) in acc {
-                        if damaged_spring
-                            && idx < damaged_groups.len()
-                            && amount < damaged_groups[idx]
-                        {
-                            // One more damaged spring in the current group
-                            increment_permutations(idx, amount + 1, permutations);
-                        } else if !damaged_spring && amount == 0 {
-                            // One more undamaged spring (and we're not in the middle of a group of damaged springs)
-                            increment_permutations(idx, 0, permutations);
-                        } else if !damaged_spring && amount == damaged_groups[idx] {
-                            // The current group of damaged springs ends with this undamaged spring
-                            increment_permutations(idx + 1, 0, permutations);
-                        }
-                    }
-                }
-            };
+    let mut permutation_counts = vec![T::zero(); states.len()];
+    permutation_counts[states.index(DamagedGroup::START)] = T::one();
+    let mut next_permutation_counts = vec![T::zero(); states.len()];
 
-            permutation_counts
+    for &spring in springs {
+        count_next(
+            &states,
+            damaged_groups,
+            spring,
+            &permutation_counts,
+            &mut next_permutation_counts,
+        );
+        std::mem::swap(&mut permutation_counts, &mut next_permutation_counts);
+    }
+
+    states
+        .states()
+        .filter(|group| group.is_complete(damaged_groups))
+        .fold(T::zero(), |mut acc, group| {
+            acc += &permutation_counts[states.index(group)];
+            acc
         })
-        .iter()
-        .filter_map(|(&DamagedGroup { idx, amount }, &permutations)| {
-            // We count only the cases where we correctly reached the end:
-            // - we went past the last group of damaged springs and didn't count any more damaged spring
-            // - we're on the last group of damaged springs and we have the expected amount of those
-            if (idx == damaged_groups.len() - 1 && amount == *damaged_groups.last().unwrap())
-                || (idx == damaged_groups.len() && amount == 0)
-            {
-                Some(permutations)
-            } else {
-                None
+}
+
+/// Count the permutations leading to each state after `spring`, from the permutations leading to
+/// each state before it
+fn count_next<T: Count>(
+    states: &StateTable,
+    damaged_groups: &[usize],
+    spring: Option<bool>,
+    permutation_counts: &[T],
+    next_permutation_counts: &mut [T],
+) {
+    next_permutation_counts.fill(T::zero());
+    for group in states.states() {
+        let permutations = &permutation_counts[states.index(group)];
+        if !permutations.is_zero() {
+            for (next_group, _) in group.next(spring, damaged_groups) {
+                next_permutation_counts[states.index(next_group)] += permutations;
             }
-        })
-        .sum()
+        }
+    }
 }
 
 fn parse_input(input: &str) -> Vec<Record> {
@@ -126,34 +187,109 @@ fn parse_input(input: &str) -> Vec<Record> {
         .collect_vec()
 }
 
-fn unfold(record: &Record) -> Record {
-    let mut springs = Vec::new();
-    for i in 1..=5 {
-        record.springs.iter().for_each(|&v| springs.push(v));
-        if i != 5 {
-            springs.push(None);
-        }
+/// `factor` copies of the record, with the copies of the springs separated by a `separator` spring
+fn unfold(record: &Record, factor: usize, separator: Option<bool>) -> Record {
+    let springs = vec![record.springs.clone(); factor]
+        .join(&separator)
+        .into_iter()
+        .collect_vec();
+    let damaged_groups = record.damaged_groups.repeat(factor);
+    Record { springs, damaged_groups }
+}
+
+/// Dense indexing of the `DamagedGroup` states of a record: the states for group `idx` go from
+/// `offsets[idx]` (amount = 0) to `offsets[idx] + damaged_groups[idx]`, and the single state past
+/// the last group is at the very end.
+struct StateTable {
+    offsets: Vec<usize>,
+}
+
+impl StateTable {
+    fn new(damaged_groups: &[usize]) -> StateTable {
+        let offsets = std::iter::once(0)
+            .chain(damaged_groups.iter().scan(0, |offset, &group_size| {
+                *offset += group_size + 1;
+                Some(*offset)
+            }))
+            .collect_vec();
+        StateTable { offsets }
     }
 
-    let mut damaged_groups = Vec::new();
-    for _ in 1..=5 {
-        record
-            .damaged_groups
-            .iter()
-            .for_each(|&g| damaged_groups.push(g));
+    fn len(&self) -> usize {
+        self.offsets.last().unwrap() + 1
     }
 
-    Record { springs, damaged_groups }
+    fn index(&self, DamagedGroup { idx, amount }: DamagedGroup) -> usize {
+        self.offsets[idx] + amount
+    }
+
+    fn states(&self) -> impl Iterator<Item = DamagedGroup> + '_ {
+        self.offsets
+            .iter()
+            .enumerate()
+            .flat_map(move |(idx, &offset)| {
+                let group_size = self
+                    .offsets
+                    .get(idx + 1)
+                    .map_or(0, |&next| next - offset - 1);
+                (0..=group_size).map(move |amount| DamagedGroup { idx, amount })
+            })
+    }
 }
 
 /// idx: index of this group of broken springs
 /// amount: number of broken springs already counted in this group
-#[derive(PartialEq, Eq, Hash, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 struct DamagedGroup {
     idx: usize,
     amount: usize,
 }
 
+impl DamagedGroup {
+    const START: DamagedGroup = DamagedGroup { idx: 0, amount: 0 };
+
+    /// Possible states after the next spring, and whether that spring is damaged in each case
+    fn next(
+        self,
+        spring: Option<bool>,
+        damaged_groups: &[usize],
+    ) -> impl Iterator<Item = (DamagedGroup, bool)> {
+        let DamagedGroup { idx, amount } = self;
+
+        // amount = 0: we can add a damaged spring (1) or an undamaged spring (2)
+        // 0 < amount < group_size: we're in the middle of a group, we can only add a damaged spring (1)
+        // amount = group_size: we've reached the end of a group, we can only add an undamaged spring and must increment the index (3)
+
+        let damaged =
+            (spring != Some(false) && idx < damaged_groups.len() && amount < damaged_groups[idx])
+                // 1
+                .then_some((DamagedGroup { idx, amount: amount + 1 }, true));
+
+        let undamaged = match spring {
+            Some(true) => None,
+            // 2
+            _ if amount == 0 => Some((DamagedGroup { idx, amount: 0 }, false)),
+            // 3
+            _ if amount == damaged_groups[idx] => {
+                Some((DamagedGroup { idx: idx + 1, amount: 0 }, false))
+            }
+            _ => None,
+        };
+
+        damaged.into_iter().chain(undamaged)
+    }
+
+    /// We count only the cases where we correctly reached the end:
+    /// - we went past the last group of damaged springs and didn't count any more damaged spring
+    /// - we're on the last group of damaged springs and we have the expected amount of those
+    fn is_complete(self, damaged_groups: &[usize]) -> bool {
+        let DamagedGroup { idx, amount } = self;
+        (idx + 1 == damaged_groups.len() && amount == damaged_groups[idx])
+            || (idx == damaged_groups.len() && amount == 0)
+    }
+}
+
+#[derive(Clone, Debug)]
 struct Record {
     springs: Vec<Option<bool>>,
     damaged_groups: Vec<usize>,
@@ -162,6 +298,7 @@ struct Record {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     static TEST_INPUT: &str = "???.### 1,1,3
 .??..??...?##. 1,1,3
@@ -179,4 +316,131 @@ mod tests {
     fn test_part_2() {
         assert_eq!(part_2(&parse_input(TEST_INPUT)), 525152);
     }
+
+    #[test]
+    fn test_unfold() {
+        let record = parse_input(".# 1").pop().unwrap();
+        let unfolded = unfold(&record, 3, Some(false));
+        assert_eq!(
+            unfolded.springs,
+            vec![
+                Some(false),
+                Some(true),
+                Some(false),
+                Some(false),
+                Some(true),
+                Some(false),
+                Some(false),
+                Some(true)
+            ]
+        );
+        assert_eq!(unfolded.damaged_groups, vec![1, 1, 1]);
+
+        let record = parse_input("???.### 1,1,3").pop().unwrap();
+        assert_eq!(count_arrangements::<u128>(&unfold(&record, 1, None)), 1);
+        assert_eq!(
+            count_arrangements::<u128>(&unfold(&record, 3, Some(false))),
+            1
+        );
+    }
+
+    #[test]
+    fn test_large_unfold() {
+        // With undamaged separators, the copies are independent: 10^50 arrangements, too many for a u128
+        let record = parse_input("?###???????? 3,2,1").pop().unwrap();
+        let count: BigUint = count_arrangements(&unfold(&record, 50, Some(false)));
+        assert_eq!(count, BigUint::from(10u32).pow(50));
+        let records = parse_input(TEST_INPUT);
+        assert_eq!(
+            count_unfolded_arrangements::<BigUint>(&records, 5),
+            BigUint::from(525152u32)
+        );
+    }
+
+    #[test]
+    fn test_generated_records() {
+        use common::gen::springs::{spring_records, SpringParams};
+        let params = SpringParams { records: 200, ..SpringParams::default() };
+        let generated = spring_records(&mut common::gen::rng(12), params);
+        let records = parse_input(&generated.input);
+        for (record, &expected) in records.iter().zip(&generated.arrangements) {
+            assert_eq!(count_arrangements::<u128>(record), expected as u128);
+        }
+    }
+
+    /// Try every assignment of the unknown springs
+    fn enumerate_arrangements(record: &Record) -> Vec<Vec<bool>> {
+        let unknown = record.springs.iter().filter(|s| s.is_none()).count();
+        (0..1u32 << unknown)
+            .map(|assignment| {
+                let mut bit = 0;
+                record
+                    .springs
+                    .iter()
+                    .map(|spring| {
+                        spring.unwrap_or_else(|| {
+                            bit += 1;
+                            assignment & (1 << (bit - 1)) != 0
+                        })
+                    })
+                    .collect_vec()
+            })
+            .filter(|springs| damaged_groups(springs) == record.damaged_groups)
+            .collect()
+    }
+
+    fn damaged_groups(springs: &[bool]) -> Vec<usize> {
+        springs
+            .split(|&damaged| !damaged)
+            .map(|group| group.len())
+            .filter(|&len| len > 0)
+            .collect()
+    }
+
+    /// Records with up to `max_len` springs. The groups usually come from a hidden arrangement (so that
+    /// there is at least one), and are sometimes random (so that there may be none).
+    fn record(max_len: usize) -> impl Strategy<Value = Record> {
+        (
+            prop::collection::vec((any::<bool>(), prop::bool::weighted(0.6)), 1..=max_len),
+            prop::option::weighted(0.2, prop::collection::vec(1..=4usize, 0..=4)),
+        )
+            .prop_map(|(springs, random_groups)| {
+                let hidden = springs.iter().map(|&(damaged, _)| damaged).collect_vec();
+                Record {
+                    springs: springs
+                        .iter()
+                        .map(|&(damaged, unknown)| (!unknown).then_some(damaged))
+                        .collect(),
+                    damaged_groups: random_groups.unwrap_or_else(|| damaged_groups(&hidden)),
+                }
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn count_arrangements_matches_enumeration(record in record(14)) {
+            let expected = enumerate_arrangements(&record);
+            prop_assert_eq!(count_arrangements::<u128>(&record), expected.len() as u128);
+
+            let arrangements = Arrangements::<u128>::new(&record);
+            prop_assert_eq!(*arrangements.count(), expected.len() as u128);
+            prop_assert_eq!(arrangements.iter().sorted().collect_vec(), expected.into_iter().sorted().collect_vec());
+        }
+
+        #[test]
+        fn unfolded_count_matches_enumeration(
+            (record, factor) in (1..=3usize).prop_flat_map(|factor| (record(15 / factor), Just(factor)))
+        ) {
+            let unfolded = unfold(&record, factor, None);
+            prop_assert_eq!(
+                count_arrangements::<u128>(&unfolded),
+                enumerate_arrangements(&unfolded).len() as u128
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_input(TEST_INPUT));
+    }
 }
@@ -1,95 +1,66 @@
 use common::itertools::Itertools;
+use common::Solution;
 
 static INPUT: &str = include_str!("input");
 
+struct Day13;
+
+impl Solution for Day13 {}
+
 fn main() {
     let patterns = parse_input(INPUT);
 
-    let res1 = part_1(&patterns);
-    println!("Part 1 result: {}", res1);
+    let res1 = part_1(&patterns).expect("no symmetry found");
+    Day13::print_answer(1, res1);
 
-    let res2 = part_2(&patterns);
-    println!("Part 2 result: {}", res2);
+    let res2 = part_2(&patterns).expect("no smudged symmetry found");
+    Day13::print_answer(2, res2);
 }
 
-fn part_1(patterns: &[Pattern]) -> usize {
-    patterns
-        .iter()
-        .flat_map(|pattern| find_symmetries(pattern))
-        .sum()
+fn part_1(patterns: &[Pattern]) -> Option<usize> {
+    summarize(patterns, 0)
+}
+
+fn part_2(patterns: &[Pattern]) -> Option<usize> {
+    summarize(patterns, 1)
 }
 
-fn part_2(patterns: &[Pattern]) -> usize {
+/// `None` if any pattern has no symmetry with this amount of smudges
+fn summarize(patterns: &[Pattern], smudges: u32) -> Option<usize> {
     patterns
         .iter()
-        .map(|pattern| find_smudged_symmetry(pattern))
+        .map(|pattern| find_symmetry(pattern, smudges).map(|s| s.summary()))
         .sum()
 }
 
-fn find_smudged_symmetry(pattern: &Pattern) -> usize {
-    let initial_symmetries = find_symmetries(pattern);
-
-    let mut p = pattern.clone();
-    for y in 0..p.height {
-        for x in 0..p.width {
-            let init_tile = p.tiles[y][x];
-            p.tiles[y][x] = !init_tile;
-            let new_symmetries = find_symmetries(&p)
-                .into_iter()
-                .filter(|s| !initial_symmetries.contains(s))
-                .collect_vec();
-            if !new_symmetries.is_empty() {
-                return new_symmetries[0];
-            }
-            // reset modified tile before trying another one
-            p.tiles[y][x] = init_tile;
-        }
-    }
-    // println!("NOT FOUND");
-    // println!("initial symmetry: {}", initial_symmetry);
-    // print_pattern(pattern);
-    panic!();
+/// Find a symmetry line, such that exactly `smudges` tiles must be flipped for the pattern to be
+/// perfectly symmetrical (horizontal lines are searched first).
+fn find_symmetry(pattern: &Pattern, smudges: u32) -> Option<Symmetry> {
+    find_symmetry_line(&pattern.rows, smudges)
+        .map(Symmetry::Horizontal)
+        .or_else(|| find_symmetry_line(&pattern.columns, smudges).map(Symmetry::Vertical))
 }
 
-fn find_symmetries(pattern: &Pattern) -> Vec<usize> {
-    find_horiz_symmetry_line(pattern)
-        .into_iter()
-        .map(|y| 100 * (y + 1))
-        .chain(
-            find_horiz_symmetry_line(&pattern.transpose())
-                .into_iter()
-                .map(|x| x + 1),
-        )
-        .collect()
-}
-
-fn find_horiz_symmetry_line(pattern: &Pattern) -> Vec<usize> {
-    let mut res = Vec::new();
-    for y in 0..(pattern.height - 1) {
-        let mut offset = 0;
-        let mut is_symmetry = false;
-        while (offset <= y) && (y + offset <= pattern.height - 2) {
-            let up = &pattern.tiles[y - offset];
-            let down = &pattern.tiles[y + offset + 1];
-            is_symmetry = up == down;
-            if !is_symmetry {
-                break;
-            } else {
-                offset += 1;
+/// Index of the first line (row or column) after the symmetry line
+fn find_symmetry_line(lines: &[Line], smudges: u32) -> Option<usize> {
+    (1..lines.len()).find(|&idx| {
+        let mut mismatches = 0;
+        for (before, after) in lines[..idx].iter().rev().zip(&lines[idx..]) {
+            mismatches += before
+                .iter()
+                .zip(after)
+                .map(|(before, after)| (before ^ after).count_ones())
+                .sum::<u32>();
+            if mismatches > smudges {
+                return false;
             }
         }
-        if is_symmetry {
-            res.push(y);
-        }
-    }
-    res
+        mismatches == smudges
+    })
 }
 
 fn parse_input(input: &str) -> Vec<Pattern> {
-    input
-        .split("\n\n")
-        .map(|pattern_str| parse_pattern(pattern_str))
-        .collect()
+    input.split("\n\n").map(parse_pattern).collect()
 }
 
 fn parse_pattern(pattern_str: &str) -> Pattern {
@@ -97,50 +68,51 @@ fn parse_pattern(pattern_str: &str) -> Pattern {
         .lines()
         .map(|line| line.chars().map(|c| c == '#').collect_vec())
         .collect_vec();
-    let height = tiles.len();
     let width = tiles[0].len();
-    Pattern { height, width, tiles }
+
+    let to_line = |bits: &[bool]| {
+        bits.chunks(64)
+            .map(|word| word.iter().fold(0, |acc, &bit| (acc << 1) | bit as u64))
+            .collect_vec()
+    };
+    let rows = tiles.iter().map(|row| to_line(row)).collect_vec();
+    let columns = (0..width)
+        .map(|x| to_line(&tiles.iter().map(|row| row[x]).collect_vec()))
+        .collect_vec();
+    Pattern { rows, columns }
 }
 
-impl Pattern {
-    fn transpose(&self) -> Pattern {
-        let height = self.width;
-        let width = self.height;
-        let mut tiles = Vec::new();
-        for y in 0..height {
-            let mut row = Vec::new();
-            for x in 0..width {
-                row.push(self.tiles[x][y]);
-            }
-            tiles.push(row);
-        }
-        Pattern { height, width, tiles }
-    }
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Symmetry {
+    /// Number of rows above the symmetry line
+    Horizontal(usize),
+    /// Number of columns left of the symmetry line
+    Vertical(usize),
 }
 
-fn print_pattern(pattern: &Pattern) {
-    for row in pattern.tiles.iter() {
-        for &tile in row {
-            if tile {
-                print!("#")
-            } else {
-                print!(".")
-            }
+impl Symmetry {
+    fn summary(&self) -> usize {
+        match *self {
+            Symmetry::Horizontal(rows) => 100 * rows,
+            Symmetry::Vertical(columns) => columns,
         }
-        println!();
     }
 }
 
-#[derive(Clone)]
+/// Each row and each column is stored as a bitmask (`1` for a rock)
+#[derive(Debug)]
 struct Pattern {
-    height: usize,
-    width: usize,
-    tiles: Vec<Vec<bool>>,
+    rows: Vec<Line>,
+    columns: Vec<Line>,
 }
 
+/// Bitmask of a row or a column, split into words of 64 tiles
+type Line = Vec<u64>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     static TEST_INPUT: &str = "#.##..##.
 ..#.##.#.
@@ -158,78 +130,164 @@ mod tests {
 ..##..###
 #....#..#";
 
-    //     #[test]
-    //     fn test_horiz() {
-    //         assert_eq!(
-    //             find_horiz_symmetry_line(&parse_pattern(
-    //                 "#...##..#
-    // #....#..#
-    // ..##..###
-    // #####.##.
-    // #####.##.
-    // ..##..###
-    // #....#..#"
-    //             )),
-    //             Some(3)
-    //         );
-    //     }
-    //
-    //     #[test]
-    //     fn test_vert() {
-    //         assert_eq!(
-    //             find_horiz_symmetry_line(
-    //                 &parse_pattern(
-    //                     "#.##..##.
-    // ..#.##.#.
-    // ##......#
-    // ##......#
-    // ..#.##.#.
-    // ..##..##.
-    // #.#.##.#."
-    //                 )
-    //                 .transpose()
-    //             ),
-    //             Some(4)
-    //         );
-    //     }
-
-    //     #[test]
-    //     fn test_smudged_1() {
-    //         assert_eq!(
-    //             find_smudged_symmetry(&parse_pattern(
-    //                 "#.##..##.
-    // ..#.##.#.
-    // ##......#
-    // ##......#
-    // ..#.##.#.
-    // ..##..##.
-    // #.#.##.#.")),
-    //             300
-    //         );
-    //     }
-    //
-    //     #[test]
-    //     fn test_smudged_2() {
-    //         assert_eq!(
-    //             find_smudged_symmetry(&parse_pattern(
-    //                 "#...##..#
-    // #....#..#
-    // ..##..###
-    // #####.##.
-    // #####.##.
-    // ..##..###
-    // #....#..#")),
-    //             100
-    //         );
-    //     }
+    #[test]
+    fn test_find_symmetry() {
+        let patterns = parse_input(TEST_INPUT);
+        assert_eq!(find_symmetry(&patterns[0], 0), Some(Symmetry::Vertical(5)));
+        assert_eq!(
+            find_symmetry(&patterns[1], 0),
+            Some(Symmetry::Horizontal(4))
+        );
+    }
+
+    #[test]
+    fn test_find_smudged_symmetry() {
+        let patterns = parse_input(TEST_INPUT);
+        assert_eq!(
+            find_symmetry(&patterns[0], 1),
+            Some(Symmetry::Horizontal(3))
+        );
+        assert_eq!(
+            find_symmetry(&patterns[1], 1),
+            Some(Symmetry::Horizontal(1))
+        );
+    }
+
+    #[test]
+    fn test_find_symmetry_several_smudges() {
+        let pattern = parse_pattern(
+            "#.#.
+..##
+..#.",
+        );
+        assert_eq!(find_symmetry(&pattern, 0), None);
+        assert_eq!(find_symmetry(&pattern, 1), Some(Symmetry::Horizontal(2)));
+        assert_eq!(find_symmetry(&pattern, 2), Some(Symmetry::Horizontal(1)));
+        assert_eq!(find_symmetry(&pattern, 3), None);
+        assert_eq!(find_symmetry(&pattern, 5), Some(Symmetry::Vertical(2)));
+        assert_eq!(summarize(&[pattern], 0), None);
+    }
+
+    #[test]
+    fn test_large_pattern() {
+        // 70 columns and 130 rows (more than one word per line), mirrored after row 100. Each row
+        // holds its index twice, so that any two different rows differ by at least 2 tiles.
+        let row = |n: usize| {
+            (0..70)
+                .map(|x| match x {
+                    0..=6 | 60..=66 if (n >> (x % 60)) & 1 == 1 => '#',
+                    _ => '.',
+                })
+                .collect::<String>()
+        };
+        let mut rows = (0..130)
+            .map(|y| row(if y < 100 { y } else { 199 - y }))
+            .collect_vec();
+        let pattern = parse_pattern(&rows.join("\n"));
+        assert_eq!((pattern.rows[0].len(), pattern.columns[0].len()), (2, 3));
+        assert_eq!(find_symmetry(&pattern, 0), Some(Symmetry::Horizontal(100)));
+
+        // Row 120 mirrors row 79 (0b1001111), which has no rock in column 65
+        rows[120].replace_range(65..66, "#");
+        let pattern = parse_pattern(&rows.join("\n"));
+        assert_eq!(find_symmetry(&pattern, 1), Some(Symmetry::Horizontal(100)));
+    }
 
     #[test]
     fn test_part_1() {
-        assert_eq!(part_1(&parse_input(TEST_INPUT)), 405);
+        assert_eq!(part_1(&parse_input(TEST_INPUT)), Some(405));
+    }
+
+    #[test]
+    fn test_part_2() {
+        assert_eq!(part_2(&parse_input(TEST_INPUT)), Some(400));
+    }
+
+    /// Rows above (or columns left of) each perfect symmetry line
+    fn perfect_symmetries(tiles: &[Vec<bool>]) -> Vec<Symmetry> {
+        let (height, width) = (tiles.len(), tiles[0].len());
+        let horizontal = (1..height)
+            .filter(|&rows| {
+                (0..rows.min(height - rows)).all(|d| tiles[rows - 1 - d] == tiles[rows + d])
+            })
+            .map(Symmetry::Horizontal);
+        let vertical = (1..width)
+            .filter(|&columns| {
+                (0..columns.min(width - columns)).all(|d| {
+                    tiles
+                        .iter()
+                        .all(|row| row[columns - 1 - d] == row[columns + d])
+                })
+            })
+            .map(Symmetry::Vertical);
+        horizontal.chain(vertical).collect()
+    }
+
+    /// The first perfect symmetry (without smudges), or the first new perfect symmetry after
+    /// flipping any single tile (with one smudge); only 0 or 1 smudge is supported
+    fn find_symmetry_naive(tiles: &[Vec<bool>], smudges: u32) -> Option<Symmetry> {
+        let original = perfect_symmetries(tiles);
+        match smudges {
+            0 => original.first().copied(),
+            1 => {
+                let mut flipped = tiles.to_vec();
+                let mut smudged = Vec::new();
+                for y in 0..tiles.len() {
+                    for x in 0..tiles[y].len() {
+                        flipped[y][x] = !flipped[y][x];
+                        smudged.extend(perfect_symmetries(&flipped));
+                        flipped[y][x] = !flipped[y][x];
+                    }
+                }
+                // Horizontal lines first, as in `find_symmetry`
+                smudged
+                    .into_iter()
+                    .filter(|s| !original.contains(s))
+                    .min_by_key(|s| match *s {
+                        Symmetry::Horizontal(rows) => (0, rows),
+                        Symmetry::Vertical(columns) => (1, columns),
+                    })
+            }
+            _ => unreachable!("the strategies only generate 0 or 1 smudge, as in the puzzle"),
+        }
+    }
+
+    /// Patterns of up to 10×10 tiles, with rows taken from a small pool (so that symmetries are
+    /// likely), and a few tiles flipped
+    fn tiles() -> impl Strategy<Value = Vec<Vec<bool>>> {
+        (1..=10usize, 1..=10usize)
+            .prop_flat_map(|(width, height)| {
+                (
+                    prop::collection::vec(prop::collection::vec(any::<bool>(), width), 1..=3),
+                    prop::collection::vec(any::<prop::sample::Index>(), height),
+                    prop::collection::vec((0..height, 0..width), 0..=2),
+                )
+            })
+            .prop_map(|(pool, rows, flips)| {
+                let mut tiles = rows.iter().map(|idx| idx.get(&pool).clone()).collect_vec();
+                for (y, x) in flips {
+                    tiles[y][x] = !tiles[y][x];
+                }
+                tiles
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn find_symmetry_matches_tile_flipping(tiles in tiles(), smudges in 0..=1u32) {
+            let input = tiles
+                .iter()
+                .map(|row| row.iter().map(|&rock| if rock { '#' } else { '.' }).collect::<String>())
+                .join("\n");
+            prop_assert_eq!(
+                find_symmetry(&parse_pattern(&input), smudges),
+                find_symmetry_naive(&tiles, smudges)
+            );
+        }
     }
 
-    // #[test]
-    // fn test_part_2() {
-    //     assert_eq!(part_2(&parse_input(TEST_INPUT)), 400);
-    // }
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_input(TEST_INPUT));
+    }
 }
@@ -1,16 +1,21 @@
 use common::itertools::Itertools;
 use common::time_execution;
+use common::Solution;
 
 static INPUT: &str = include_str!("input");
 
+struct Day14;
+
+impl Solution for Day14 {}
+
 fn main() {
     let platform = parse_input(INPUT);
 
     let res1 = part_1(&platform);
-    println!("Part 1 result: {}", res1);
+    Day14::print_answer(1, res1);
 
     let res2 = time_execution("part 2", || part_2(&platform));
-    println!("Part 2 result: {}", res2);
+    Day14::print_answer(2, res2);
 }
 
 fn part_1(platform: &Platform) -> usize {
@@ -197,6 +202,64 @@ O.#..O.#.#
         assert_eq!(part_2(&parse_input(TEST_INPUT)), 64);
     }
 
+    /// Load on the north beams, counting for each column the round rocks which roll up to each
+    /// cube-shaped rock (or to the north edge)
+    fn load_naive(input: &str) -> usize {
+        let rows = input.lines().map(|l| l.as_bytes()).collect_vec();
+        let height = rows.len();
+        (0..rows[0].len())
+            .map(|x| {
+                let mut load = 0;
+                let mut free_y = 0;
+                for (y, row) in rows.iter().enumerate() {
+                    match row[x] {
+                        b'#' => free_y = y + 1,
+                        b'O' => {
+                            load += height - free_y;
+                            free_y += 1;
+                        }
+                        _ => {}
+                    }
+                }
+                load
+            })
+            .sum()
+    }
+
+    fn cube_rocks(platform: &Platform) -> Vec<Vec<bool>> {
+        platform
+            .tiles
+            .iter()
+            .map(|row| row.iter().map(|&t| t == Tile::CubeRock).collect_vec())
+            .collect_vec()
+    }
+
+    fn round_rocks_count(platform: &Platform) -> usize {
+        platform
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|&&t| t == Tile::RoundRock)
+            .count()
+    }
+
+    #[test]
+    fn test_generated_platforms() {
+        let mut rng = common::gen::rng(14);
+        for (width, height, round, cube) in
+            [(1, 1, 0.5, 0.2), (7, 13, 0.3, 0.1), (100, 100, 0.2, 0.2)]
+        {
+            let input = common::gen::platform::platform(&mut rng, width, height, round, cube);
+            let platform = parse_input(&input);
+            assert_eq!((platform.width, platform.height), (width, height));
+            assert_eq!(part_1(&platform), load_naive(&input));
+
+            let cycled = next_cycle(&platform);
+            assert_eq!(round_rocks_count(&cycled), round_rocks_count(&platform));
+            assert_eq!(cube_rocks(&cycled), cube_rocks(&platform));
+        }
+    }
+
     #[test]
     fn test_cycle() {
         let p0 = parse_input(TEST_INPUT);
@@ -252,4 +315,9 @@ O.#..O.#.#
             )
         );
     }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_input(TEST_INPUT));
+    }
 }
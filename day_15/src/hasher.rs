@@ -0,0 +1,171 @@
+use std::hash::{BuildHasher, Hasher};
+
+/// A member of the HASH family: for each byte, `state = (state + byte) * multiplier % modulus`,
+/// starting from `seed`.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct HashParams {
+    pub multiplier: u64,
+    /// Number of buckets, which must be at least 1 (hashing or counting the buckets panics
+    /// otherwise)
+    pub modulus: u64,
+    pub seed: u64,
+}
+
+impl HashParams {
+    /// The Holiday ASCII String Helper algorithm from the puzzle
+    pub const PUZZLE: HashParams = HashParams { multiplier: 17, modulus: 256, seed: 0 };
+
+    /// Hash of the raw bytes of the string (unlike `str::hash`, which also writes a terminator).
+    pub fn hash(&self, s: &str) -> usize {
+        let mut hasher = self.build_hasher();
+        hasher.write(s.as_bytes());
+        hasher.finish() as usize
+    }
+
+    pub fn buckets(&self) -> usize {
+        self.check_modulus();
+        self.modulus as usize
+    }
+
+    fn check_modulus(&self) {
+        assert!(
+            self.modulus > 0,
+            "HASH parameters without any bucket: {:?}",
+            self
+        );
+    }
+}
+
+impl BuildHasher for HashParams {
+    type Hasher = HolidayHasher;
+
+    fn build_hasher(&self) -> HolidayHasher {
+        self.check_modulus();
+        HolidayHasher { params: *self, state: self.seed % self.modulus }
+    }
+}
+
+pub struct HolidayHasher {
+    params: HashParams,
+    state: u64,
+}
+
+impl Hasher for HolidayHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let HashParams { multiplier, modulus, .. } = self.params;
+        for &b in bytes {
+            let state = (self.state as u128 + b as u128) * multiplier as u128 % modulus as u128;
+            self.state = state as u64;
+        }
+    }
+}
+
+/// How a set of distinct labels spreads over the buckets of a hash function.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Distribution {
+    /// Number of labels in each bucket
+    pub loads: Vec<usize>,
+}
+
+impl Distribution {
+    pub fn new<'a>(params: &HashParams, labels: impl IntoIterator<Item = &'a str>) -> Distribution {
+        let mut labels = labels.into_iter().collect::<Vec<_>>();
+        labels.sort_unstable();
+        labels.dedup();
+        let mut loads = vec![0; params.buckets()];
+        for label in labels {
+            loads[params.hash(label)] += 1;
+        }
+        Distribution { loads }
+    }
+
+    pub fn labels(&self) -> usize {
+        self.loads.iter().sum()
+    }
+
+    pub fn used_buckets(&self) -> usize {
+        self.loads.iter().filter(|&&load| load > 0).count()
+    }
+
+    pub fn max_load(&self) -> usize {
+        self.loads.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Pairs of distinct labels which end up in the same bucket
+    pub fn colliding_pairs(&self) -> usize {
+        self.loads
+            .iter()
+            .map(|&load| load * load.saturating_sub(1) / 2)
+            .sum()
+    }
+
+    /// Colliding pairs expected from a uniformly random hash function
+    pub fn expected_colliding_pairs(&self) -> f64 {
+        let n = self.labels() as f64;
+        n * (n - 1.0) / 2.0 / self.loads.len() as f64
+    }
+
+    /// Pearson's χ² statistic against a uniform distribution (around the number of buckets minus
+    /// one for a good hash function).
+    pub fn chi_squared(&self) -> f64 {
+        let expected = self.labels() as f64 / self.loads.len() as f64;
+        self.loads
+            .iter()
+            .map(|&load| (load as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_puzzle_params() {
+        assert_eq!(HashParams::PUZZLE.hash("HASH"), 52);
+        assert_eq!(HashParams::PUZZLE.hash("rn"), 0);
+        assert_eq!(HashParams::PUZZLE.hash(""), 0);
+
+        let seeded = HashParams { seed: 3, ..HashParams::PUZZLE };
+        assert_eq!(seeded.hash(""), 3);
+        assert_eq!(seeded.hash("a"), (3 + 97) * 17 % 256);
+
+        // Large parameters must not overflow
+        let large = HashParams { multiplier: u64::MAX, modulus: u64::MAX - 1, seed: u64::MAX };
+        assert!(large.hash("HASH") < large.buckets());
+    }
+
+    #[test]
+    #[should_panic(expected = "HASH parameters without any bucket")]
+    fn test_no_bucket() {
+        HashParams { modulus: 0, ..HashParams::PUZZLE }.hash("HASH");
+    }
+
+    #[test]
+    fn test_build_hasher() {
+        // Usable as the hasher of a std `HashMap`, even if it's a poor choice there
+        let mut map = HashMap::with_hasher(HashParams::PUZZLE);
+        map.insert("rn", 1);
+        map.insert("cm", 2);
+        assert_eq!(map.get("cm"), Some(&2));
+    }
+
+    #[test]
+    fn test_distribution() {
+        let distribution = Distribution::new(
+            &HashParams::PUZZLE,
+            ["rn", "cm", "qp", "cm", "pc", "ot", "ab"],
+        );
+        assert_eq!(distribution.labels(), 6);
+        assert_eq!(distribution.used_buckets(), 3);
+        assert_eq!(distribution.max_load(), 3);
+        // rn & cm in box 0, pc & ot & ab in box 3
+        assert_eq!(distribution.colliding_pairs(), 1 + 3);
+        assert_eq!(distribution.expected_colliding_pairs(), 15.0 / 256.0);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::hasher::HashParams;
+use crate::{Lens, Operation};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Lens boxes, with one box per bucket of the hash function.
+#[derive(Clone, Debug)]
+pub struct LensBoxes {
+    hash_params: HashParams,
+    boxes: Vec<LensBox>,
+}
+
+impl LensBoxes {
+    /// The 256 boxes of the puzzle
+    pub fn new() -> LensBoxes {
+        LensBoxes::with_hash(HashParams::PUZZLE)
+    }
+
+    pub fn with_hash(hash_params: HashParams) -> LensBoxes {
+        LensBoxes {
+            hash_params,
+            boxes: vec![LensBox::default(); hash_params.buckets()],
+        }
+    }
+
+    pub fn apply(&mut self, operation: Operation) {
+        let lens_box = &mut self.boxes[self.hash_params.hash(operation.label())];
+        match operation {
+            Operation::Remove { label } => lens_box.remove(&label),
+            Operation::Add { lens } => lens_box.insert(lens),
+        }
+    }
+
+    /// State of the boxes after each of the operations, starting from the current state.
+    pub fn snapshots(&self, operations: impl IntoIterator<Item = Operation>) -> Vec<LensBoxes> {
+        let mut lens_boxes = self.clone();
+        operations
+            .into_iter()
+            .map(|operation| {
+                lens_boxes.apply(operation);
+                lens_boxes.clone()
+            })
+            .collect()
+    }
+
+    /// The lens with this label, if any, without scanning the box which holds it
+    pub fn get(&self, label: &str) -> Option<&Lens> {
+        self.boxes[self.hash_params.hash(label)].get(label)
+    }
+
+    /// (box index, slot in the box, lens), in box order then in slot order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &Lens)> {
+        self.boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(box_idx, lens_box)| {
+                lens_box
+                    .lenses()
+                    .enumerate()
+                    .map(move |(slot, lens)| (box_idx, slot, lens))
+            })
+    }
+
+    pub fn focusing_power(&self) -> usize {
+        self.iter()
+            .map(|(box_idx, slot, lens)| (1 + box_idx) * (1 + slot) * lens.value)
+            .sum()
+    }
+}
+
+impl Default for LensBoxes {
+    fn default() -> Self {
+        LensBoxes::new()
+    }
+}
+
+/// Same format as the puzzle's worked example (e.g. `Box 3: [ot 9] [ab 5]`), empty boxes omitted
+impl fmt::Display for LensBoxes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (box_idx, lens_box) in self.boxes.iter().enumerate() {
+            if lens_box.len() > 0 {
+                write!(f, "Box {}:", box_idx)?;
+                for lens in lens_box.lenses() {
+                    write!(f, " [{} {}]", lens.label, lens.value)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lenses in insertion order, with removed lenses left as holes (so that the slots of the other
+/// lenses, which the index points to, stay valid) until there are too many of them.
+#[derive(Clone, Default, Debug)]
+struct LensBox {
+    slots: Vec<Option<Lens>>,
+    index: HashMap<String, usize>,
+}
+
+impl LensBox {
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    fn lenses(&self) -> impl Iterator<Item = &Lens> {
+        self.slots.iter().flatten()
+    }
+
+    fn get(&self, label: &str) -> Option<&Lens> {
+        self.index
+            .get(label)
+            .and_then(|&slot| self.slots[slot].as_ref())
+    }
+
+    fn insert(&mut self, lens: Lens) {
+        if let Some(&slot) = self.index.get(&lens.label) {
+            self.slots[slot] = Some(lens);
+        } else {
+            self.index.insert(lens.label.clone(), self.slots.len());
+            self.slots.push(Some(lens));
+        }
+    }
+
+    fn remove(&mut self, label: &str) {
+        if let Some(slot) = self.index.remove(label) {
+            self.slots[slot] = None;
+            if self.slots.len() > 2 * self.len() + 8 {
+                self.compact();
+            }
+        }
+    }
+
+    fn compact(&mut self) {
+        self.slots.retain(Option::is_some);
+        for (slot, lens) in self.slots.iter().flatten().enumerate() {
+            *self.index.get_mut(&lens.label).unwrap() = slot;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TEST_INPUT;
+    use crate::{parse_input, parse_operations};
+    use common::itertools::Itertools;
+
+    #[test]
+    fn test_snapshots() {
+        let instructions = parse_input(TEST_INPUT);
+        let snapshots = LensBoxes::new().snapshots(parse_operations(&instructions));
+        let steps = instructions
+            .iter()
+            .zip(&snapshots)
+            .map(|(instruction, boxes)| format!("After \"{}\":\n{}", instruction, boxes))
+            .join("\n");
+        assert_eq!(
+            steps,
+            "After \"rn=1\":
+Box 0: [rn 1]
+
+After \"cm-\":
+Box 0: [rn 1]
+
+After \"qp=3\":
+Box 0: [rn 1]
+Box 1: [qp 3]
+
+After \"cm=2\":
+Box 0: [rn 1] [cm 2]
+Box 1: [qp 3]
+
+After \"qp-\":
+Box 0: [rn 1] [cm 2]
+
+After \"pc=4\":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4]
+
+After \"ot=9\":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9]
+
+After \"ab=5\":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9] [ab 5]
+
+After \"pc-\":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5]
+
+After \"pc=6\":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5] [pc 6]
+
+After \"ot=7\":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 7] [ab 5] [pc 6]
+"
+        );
+        assert_eq!(snapshots.last().unwrap().focusing_power(), 145);
+    }
+
+    #[test]
+    fn test_many_removals() {
+        // "rn" and "cm" both go to box 0, which accumulates holes
+        let add =
+            |label: &str, value| Operation::Add { lens: Lens { label: label.to_owned(), value } };
+        let remove = |label: &str| Operation::Remove { label: label.to_owned() };
+        let mut lens_boxes = LensBoxes::new();
+        for value in 1..=100 {
+            lens_boxes.apply(add("rn", value));
+            lens_boxes.apply(add("cm", value));
+            lens_boxes.apply(remove("rn"));
+        }
+        lens_boxes.apply(add("rn", 3));
+        lens_boxes.apply(add("qp", 1));
+
+        assert_eq!(lens_boxes.get("cm").map(|l| l.value), Some(100));
+        assert_eq!(lens_boxes.get("pc"), None);
+        assert_eq!(
+            lens_boxes
+                .iter()
+                .map(|(box_idx, slot, lens)| (box_idx, slot, lens.label.as_str()))
+                .collect_vec(),
+            vec![(0, 0, "cm"), (0, 1, "rn"), (1, 0, "qp")]
+        );
+        assert_eq!(lens_boxes.focusing_power(), 100 + 2 * 3 + 2);
+    }
+
+    #[test]
+    fn test_bucket_count() {
+        let hash_params = HashParams { multiplier: 31, modulus: 7, seed: 1 };
+        let operations = parse_operations(&parse_input(TEST_INPUT));
+        let snapshots = LensBoxes::with_hash(hash_params).snapshots(operations.clone());
+        let mut lens_boxes = LensBoxes::with_hash(hash_params);
+        for operation in operations {
+            lens_boxes.apply(operation);
+        }
+        assert_eq!(
+            snapshots.last().unwrap().to_string(),
+            lens_boxes.to_string()
+        );
+        let content = lens_boxes
+            .iter()
+            .map(|(box_idx, slot, lens)| (box_idx, slot, lens.label.as_str(), lens.value))
+            .collect_vec();
+        assert_eq!(content.len(), 5);
+        assert!(content.iter().all(|&(box_idx, ..)| box_idx < 7));
+        assert_eq!(lens_boxes.get("ot").map(|l| l.value), Some(7));
+        assert_eq!(lens_boxes.get("qp"), None);
+    }
+}
@@ -1,17 +1,80 @@
+use crate::hasher::{Distribution, HashParams};
+use crate::lens_boxes::LensBoxes;
 use common::itertools::Itertools;
+use common::Solution;
 use sscanf::sscanf;
-use std::array;
+use std::fmt;
+
+mod hasher;
+mod lens_boxes;
 
 static INPUT: &str = include_str!("input");
 
+struct Day15;
+
+impl Solution for Day15 {}
+
 fn main() {
     let instructions = parse_input(INPUT);
 
     let res1 = part_1(&instructions);
-    println!("Part 1 result: {}", res1);
+    Day15::print_answer(1, res1);
 
     let res2 = part_2(&instructions);
-    println!("Part 2 result: {}", res2);
+    Day15::print_answer(2, res2);
+
+    if std::env::args().any(|arg| arg == "--steps") {
+        let snapshots = LensBoxes::new().snapshots(parse_operations(&instructions));
+        for (instruction, lens_boxes) in instructions.iter().zip(snapshots) {
+            print!("\nAfter \"{}\":\n{}", instruction, lens_boxes);
+        }
+    }
+
+    if std::env::args().any(|arg| arg == "--collisions") {
+        print_collisions(&parse_operations(&instructions));
+    }
+
+    // e.g. `cargo run -p day_15 -- --lens cm`
+    if let Some(label) = std::env::args().skip_while(|arg| arg != "--lens").nth(1) {
+        let mut lens_boxes = LensBoxes::new();
+        for operation in parse_operations(&instructions) {
+            lens_boxes.apply(operation);
+        }
+        match lens_boxes.get(&label) {
+            Some(lens) => println!("Lens {}: focal length {}", label, lens.value),
+            None => println!("No lens {} in the boxes", label),
+        }
+    }
+}
+
+/// Compare how well a few members of the HASH family spread the labels of the instructions.
+fn print_collisions(operations: &[Operation]) {
+    let candidates = [
+        HashParams::PUZZLE,
+        HashParams { multiplier: 31, ..HashParams::PUZZLE },
+        HashParams { modulus: 257, ..HashParams::PUZZLE },
+        HashParams { multiplier: 31, modulus: 251, seed: 7 },
+        HashParams { multiplier: 16, ..HashParams::PUZZLE },
+    ];
+    println!(
+        "\n{:>10} {:>7} {:>4} {:>6} {:>7} {:>8} {:>8} {:>8} {:>8}",
+        "multiplier", "modulus", "seed", "labels", "buckets", "max load", "pairs", "expected", "χ²"
+    );
+    for params in candidates {
+        let distribution = Distribution::new(&params, operations.iter().map(|op| op.label()));
+        println!(
+            "{:>10} {:>7} {:>4} {:>6} {:>7} {:>8} {:>8} {:>8.1} {:>8.1}",
+            params.multiplier,
+            params.modulus,
+            params.seed,
+            distribution.labels(),
+            distribution.used_buckets(),
+            distribution.max_load(),
+            distribution.colliding_pairs(),
+            distribution.expected_colliding_pairs(),
+            distribution.chi_squared()
+        );
+    }
 }
 
 fn part_1(instructions: &[&str]) -> usize {
@@ -22,50 +85,15 @@ fn part_1(instructions: &[&str]) -> usize {
 }
 
 fn part_2(instructions: &[&str]) -> usize {
-    let operations = parse_operations(instructions);
-
-    let mut boxes: [Vec<Lens>; 256] = array::from_fn(|_| Vec::new());
-    operations.into_iter().for_each(|op| {
-        let box_idx = op.lens_box();
-        match op {
-            Operation::Remove { label } => {
-                if let Some((lens_idx, _)) =
-                    boxes[box_idx].iter().find_position(|l| l.label == label)
-                {
-                    boxes[box_idx].remove(lens_idx);
-                }
-            }
-            Operation::Add { lens } => {
-                if let Some((lens_idx, _)) = boxes[box_idx]
-                    .iter()
-                    .find_position(|l| l.label == lens.label)
-                {
-                    let _ = std::mem::replace(&mut boxes[box_idx][lens_idx], lens);
-                } else {
-                    boxes[box_idx].push(lens);
-                }
-            }
-        }
-    });
-
-    boxes
-        .iter()
-        .enumerate()
-        .map(|(box_idx, lenses)| {
-            (1 + box_idx)
-                * lenses
-                    .iter()
-                    .enumerate()
-                    .map(|(lens_idx, lens)| (1 + lens_idx) * lens.value)
-                    .sum::<usize>()
-        })
-        .sum()
+    let mut lens_boxes = LensBoxes::new();
+    for operation in parse_operations(instructions) {
+        lens_boxes.apply(operation);
+    }
+    lens_boxes.focusing_power()
 }
 
 fn hash(s: &str) -> usize {
-    s.as_bytes()
-        .into_iter()
-        .fold(0, |acc, &c| (acc + c as usize) * 17 % 256)
+    HashParams::PUZZLE.hash(s)
 }
 
 fn parse_input(input: &str) -> Vec<&str> {
@@ -87,31 +115,41 @@ fn parse_operations(instructions: &[&str]) -> Vec<Operation> {
         .collect_vec()
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 struct Lens {
     label: String,
     value: usize,
 }
 
 impl Operation {
-    fn lens_box(&self) -> usize {
+    fn label(&self) -> &str {
         match self {
-            Operation::Add { lens } => hash(&lens.label),
-            Operation::Remove { label } => hash(label),
+            Operation::Add { lens } => &lens.label,
+            Operation::Remove { label } => label,
         }
     }
 }
 
+#[derive(PartialEq, Eq, Clone, Debug)]
 enum Operation {
     Add { lens: Lens },
     Remove { label: String },
 }
 
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Add { lens } => write!(f, "{}={}", lens.label, lens.value),
+            Operation::Remove { label } => write!(f, "{}-", label),
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
-    static TEST_INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+    pub static TEST_INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
 
     #[test]
     fn test_hash() {
@@ -127,4 +165,16 @@ mod tests {
     fn test_part_2() {
         assert_eq!(part_2(&parse_input(TEST_INPUT)), 145);
     }
+
+    #[test]
+    fn test_operation_display() {
+        let instructions = parse_input(TEST_INPUT);
+        let operations = parse_operations(&instructions);
+        assert_eq!(operations.iter().join(","), TEST_INPUT);
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_operations(&parse_input(TEST_INPUT)));
+    }
 }
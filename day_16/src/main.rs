@@ -1,18 +1,23 @@
 use common::itertools::Itertools;
 use common::maplit::hashset;
 use common::time_execution;
+use common::Solution;
 use std::collections::HashSet;
 
 static INPUT: &str = include_str!("input");
 
+struct Day16;
+
+impl Solution for Day16 {}
+
 fn main() {
     let cave = parse_input(INPUT);
 
     let res1 = time_execution("Part 1", || part_1(&cave));
-    println!("Part 1 result: {}", res1);
+    Day16::print_answer(1, res1);
 
     let res2 = time_execution("Part 2", || part_2(&cave));
-    println!("Part 2 result: {}", res2);
+    Day16::print_answer(2, res2);
 }
 
 fn part_1(cave: &Cave) -> usize {
@@ -115,13 +120,14 @@ fn parse_input(input: &str) -> Cave {
     Cave { height, width, tiles }
 }
 
+#[derive(Debug)]
 struct Cave {
     height: usize,
     width: usize,
     tiles: Vec<Vec<Tile>>,
 }
 
-#[derive(Hash, PartialEq, Eq, Copy, Clone)]
+#[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
 enum Tile {
     Empty,      // .
     Mirror,     // /
@@ -136,13 +142,13 @@ impl Point {
     }
 }
 
-#[derive(Hash, PartialEq, Eq, Copy, Clone)]
+#[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
 struct Point {
     x: usize,
     y: usize,
 }
 
-#[derive(Hash, PartialEq, Eq, Copy, Clone)]
+#[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
 enum Direction {
     Left,
     Right,
@@ -174,4 +180,9 @@ mod tests {
     fn test_part_2() {
         assert_eq!(part_2(&parse_input(TEST_INPUT)), 51);
     }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_input(TEST_INPUT));
+    }
 }
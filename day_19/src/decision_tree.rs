@@ -0,0 +1,139 @@
+use crate::{CategoryError, Domains, Part, RatingSet, Rule, RuleCondition, RuleResult, Workflows};
+use range_collections::RangeSet;
+use std::collections::HashMap;
+
+/// All the workflows, starting from "in", with the `ChainTo` results inlined.
+#[derive(Eq, PartialEq, Debug)]
+pub enum DecisionTree {
+    Leaf(bool),
+    Branch {
+        condition: RuleCondition,
+        matched: Box<DecisionTree>,
+        not_matched: Box<DecisionTree>,
+    },
+}
+
+impl DecisionTree {
+    pub fn compile(workflows: &Workflows) -> DecisionTree {
+        compile_rules(workflows, &workflows["in"], &Bounds::new())
+    }
+
+    /// An error if the part has no rating for a category compared on its way
+    pub fn is_accepted(&self, part: &Part) -> Result<bool, CategoryError> {
+        let mut node = self;
+        loop {
+            match node {
+                DecisionTree::Leaf(accepted) => return Ok(*accepted),
+                DecisionTree::Branch { condition, matched, not_matched } => {
+                    node = if condition.matches(part)? {
+                        matched
+                    } else {
+                        not_matched
+                    };
+                }
+            }
+        }
+    }
+
+    /// Convert back to workflows (with generated labels), e.g. to print the compiled tree.
+    pub fn to_workflows(&self) -> Workflows {
+        fn rule_result(tree: &DecisionTree, workflows: &mut Workflows) -> RuleResult {
+            match tree {
+                DecisionTree::Leaf(true) => RuleResult::Accepted,
+                DecisionTree::Leaf(false) => RuleResult::Rejected,
+                DecisionTree::Branch { .. } => {
+                    // Lowercase-only (as expected by the parser), and never equal to "in"
+                    let label = common::gen::label("t", workflows.len());
+                    add_workflow(tree, label.clone(), workflows);
+                    RuleResult::ChainTo(label)
+                }
+            }
+        }
+
+        fn add_workflow(tree: &DecisionTree, label: String, workflows: &mut Workflows) {
+            // Reserve the label before recursing, so that nested workflows get distinct labels
+            workflows.insert(label.clone(), Vec::new());
+            let mut rules = Vec::new();
+            let mut node = tree;
+            // Successive `not_matched` branches become the successive rules of a single workflow
+            while let DecisionTree::Branch { condition, matched, not_matched } = node {
+                let result = rule_result(matched, workflows);
+                rules.push(Rule { condition: condition.clone(), result });
+                node = not_matched;
+            }
+            let result = rule_result(node, workflows);
+            rules.push(Rule { condition: RuleCondition::Default, result });
+            workflows.insert(label, rules);
+        }
+
+        let mut workflows = HashMap::new();
+        add_workflow(self, "in".to_owned(), &mut workflows);
+        workflows
+    }
+}
+
+fn compile_rules(workflows: &Workflows, rules: &[Rule], bounds: &Bounds) -> DecisionTree {
+    let (rule, other_rules) = rules.split_first().unwrap();
+    let compile_result = |bounds: &Bounds| match &rule.result {
+        RuleResult::Accepted => DecisionTree::Leaf(true),
+        RuleResult::Rejected => DecisionTree::Leaf(false),
+        RuleResult::ChainTo(label) => compile_rules(workflows, &workflows[label], bounds),
+    };
+
+    if rule.condition == RuleCondition::Default {
+        return compile_result(bounds);
+    }
+
+    // Conditions already decided by the previous ones are skipped altogether
+    match (
+        bounds.restrict(&rule.condition, true),
+        bounds.restrict(&rule.condition, false),
+    ) {
+        (Some(matched_bounds), Some(not_matched_bounds)) => {
+            let matched = compile_result(&matched_bounds);
+            let not_matched = compile_rules(workflows, other_rules, &not_matched_bounds);
+            if matched == not_matched {
+                matched
+            } else {
+                DecisionTree::Branch {
+                    condition: rule.condition.clone(),
+                    matched: Box::new(matched),
+                    not_matched: Box::new(not_matched),
+                }
+            }
+        }
+        (Some(matched_bounds), None) => compile_result(&matched_bounds),
+        (None, _) => compile_rules(workflows, other_rules, bounds),
+    }
+}
+
+/// Possible ratings for each category, as implied by the conditions on the path to a node
+/// (a category which is not constrained yet can have any rating)
+#[derive(Clone)]
+struct Bounds(Domains);
+
+impl Bounds {
+    fn new() -> Bounds {
+        Bounds(Domains::new())
+    }
+
+    /// `None` if no rating within the bounds can have this outcome.
+    fn restrict(&self, condition: &RuleCondition, matched: bool) -> Option<Bounds> {
+        let RuleCondition::Compare { category, .. } = condition else {
+            return Some(self.clone());
+        };
+        let prev_ratings = self.0.get(category).cloned().unwrap_or(RangeSet::all());
+        let new_ratings: RatingSet = if matched {
+            prev_ratings.intersection(&condition.matching_ratings())
+        } else {
+            prev_ratings.difference(&condition.matching_ratings())
+        };
+        if new_ratings.is_empty() {
+            None
+        } else {
+            let mut restricted = self.clone();
+            restricted.0.insert(category.clone(), new_ratings);
+            Some(restricted)
+        }
+    }
+}
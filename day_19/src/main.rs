@@ -1,142 +1,84 @@
+mod decision_tree;
+
+use crate::decision_tree::DecisionTree;
+use common::itertools::Itertools;
 use common::maplit::hashmap;
+use common::Solution;
+use range_collections::{RangeSet, RangeSet2};
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::Bound;
+use std::ops::RangeBounds;
 
 static INPUT_PARTS: &str = include_str!("parts");
 static INPUT_WORKFLOWS: &str = include_str!("workflows");
 
-fn main() {
+struct Day19;
+
+impl Solution for Day19 {}
+
+fn main() -> Result<(), Box<dyn Error>> {
     let parts = parse_parts(INPUT_PARTS);
     let workflows = parse_workflows(INPUT_WORKFLOWS);
 
-    let res1 = part_1(&parts, &workflows);
-    println!("Part 1 result: {}", res1);
+    let res1 = part_1(&parts, &workflows)?;
+    Day19::print_answer(1, res1);
 
-    let res2 = part_2(&workflows);
-    println!("Part 2 result: {}", res2);
+    let res2 = part_2(&workflows, &xmas_domains())?;
+    Day19::print_answer(2, res2);
+
+    // e.g. `cargo run -p day_19 -- --compiled`
+    if std::env::args().any(|arg| arg == "--compiled") {
+        let decision_tree = DecisionTree::compile(&workflows);
+        println!("{}", format_workflows(&decision_tree.to_workflows()));
+    }
+
+    Ok(())
 }
 
-fn part_1(parts: &[Part], workflows: &Workflows) -> usize {
-    fn is_accepted(part: &Part, workflows: &Workflows, workflow_label: &str) -> bool {
-        let rule_result = workflows[workflow_label]
-            .iter()
-            .find(|r| match r.condition {
-                RuleCondition::GreaterThan { category, value } => {
-                    part.get_rating(&category) > value
-                }
-                RuleCondition::LessThan { category, value } => part.get_rating(&category) < value,
-                RuleCondition::Default => true,
-            })
-            .map(|r| &r.result)
-            .unwrap();
-        match rule_result {
-            RuleResult::Accepted => true,
-            RuleResult::Rejected => false,
-            RuleResult::ChainTo(label) => is_accepted(part, workflows, label),
+fn part_1(parts: &[Part], workflows: &Workflows) -> Result<usize, CategoryError> {
+    let decision_tree = DecisionTree::compile(workflows);
+    let mut total = 0;
+    for part in parts {
+        if decision_tree.is_accepted(part)? {
+            total += part.ratings.values().sum::<usize>();
         }
     }
-
-    parts
-        .iter()
-        .map(|p| {
-            if is_accepted(p, workflows, "in") {
-                p.ratings.values().sum()
-            } else {
-                0
-            }
-        })
-        .sum()
+    Ok(total)
 }
 
-fn part_2(workflows: &Workflows) -> usize {
+fn part_2(workflows: &Workflows, domains: &Domains) -> Result<usize, CategoryError> {
     fn recurs(workflows: &Workflows, workflow_label: &str, range: PartsRange) -> usize {
-        let rules = &workflows[workflow_label];
-        rules
-            .iter()
-            .fold((0, range), |(acc, remaining_range), rule| {
-                match rule.condition {
-                    RuleCondition::GreaterThan { category, value } => remaining_range
-                        .0
-                        .get(&category)
-                        .map(|rr| {
-                            let mut matched = remaining_range.clone();
-                            if rr.1 > value {
-                                matched
-                                    .0
-                                    .insert(category, RatingRange(rr.0.max(value + 1), rr.1));
-                            } else {
-                                matched.0.remove(&category);
-                            };
-
-                            let mut not_matched = remaining_range.clone();
-                            if rr.0 > value {
-                                not_matched.0.remove(&category);
-                            } else {
-                                not_matched
-                                    .0
-                                    .insert(category, RatingRange(rr.0, rr.1.min(value)));
-                            }
-
-                            match &rule.result {
-                                RuleResult::Accepted => (acc + matched.size(), not_matched),
-                                RuleResult::Rejected => (acc, not_matched),
-                                RuleResult::ChainTo(label) => {
-                                    (acc + recurs(workflows, &label, matched), not_matched)
-                                }
-                            }
-                        })
-                        .unwrap_or((0, remaining_range)),
-                    RuleCondition::LessThan { category, value } => remaining_range
-                        .0
-                        .get(&category)
-                        .map(|rr| {
-                            let mut matched = remaining_range.clone();
-                            if rr.0 < value {
-                                matched
-                                    .0
-                                    .insert(category, RatingRange(rr.0, rr.1.min(value - 1)));
-                            } else {
-                                matched.0.remove(&category);
-                            };
-
-                            let mut not_matched = remaining_range.clone();
-                            if rr.1 < value {
-                                not_matched.0.remove(&category);
-                            } else {
-                                not_matched
-                                    .0
-                                    .insert(category, RatingRange(rr.0.max(value), rr.1));
-                            }
-
-                            match &rule.result {
-                                RuleResult::Accepted => (acc + matched.size(), not_matched),
-                                RuleResult::Rejected => (acc, not_matched),
-                                RuleResult::ChainTo(label) => {
-                                    (acc + recurs(workflows, &label, matched), not_matched)
-                                }
-                            }
-                        })
-                        .unwrap_or((0, remaining_range)),
-                    RuleCondition::Default => match &rule.result {
-                        RuleResult::Accepted => (acc + remaining_range.size(), PartsRange::empty()),
-                        RuleResult::Rejected => (acc, PartsRange::empty()),
-                        RuleResult::ChainTo(label) => (
-                            acc + recurs(workflows, &label, remaining_range),
-                            PartsRange::empty(),
-                        ),
-                    },
-                }
-            })
-            .0
+        let mut acc = 0;
+        let mut remaining_range = range;
+        for rule in workflows[workflow_label].iter() {
+            if remaining_range.is_empty() {
+                break;
+            }
+            let (matched, not_matched) = remaining_range.split(&rule.condition);
+            acc += match &rule.result {
+                RuleResult::Accepted => matched.size(),
+                RuleResult::Rejected => 0,
+                RuleResult::ChainTo(label) => recurs(workflows, label, matched),
+            };
+            remaining_range = not_matched;
+        }
+        acc
     }
 
-    let initial_range = PartsRange(hashmap! {
-        Category::X => RatingRange(1, 4000),
-        Category::M => RatingRange(1, 4000),
-        Category::A => RatingRange(1, 4000),
-        Category::S => RatingRange(1, 4000),
-    });
+    let range = PartsRange::new(domains, workflows)?;
+    Ok(recurs(workflows, "in", range))
+}
 
-    recurs(workflows, "in", initial_range)
+/// The domain of each of the 4 puzzle categories is 1..=4000
+fn xmas_domains() -> Domains {
+    hashmap! {
+        "x".to_owned() => RangeSet::from(1..4001),
+        "m".to_owned() => RangeSet::from(1..4001),
+        "a".to_owned() => RangeSet::from(1..4001),
+        "s".to_owned() => RangeSet::from(1..4001),
+    }
 }
 
 fn parse_parts(input: &str) -> Vec<Part> {
@@ -151,13 +93,13 @@ peg::parser! {
     grammar workflow_parser() for str {
         rule label() -> String = l:$(['a'..='z']+) { l.to_owned() }
         rule ruleResult() -> RuleResult = "A" { RuleResult::Accepted } / "R" { RuleResult::Rejected } / l:label() { RuleResult::ChainTo(l) }
-        rule category() -> Category = "x" { Category::X } / "m" { Category::M } / "a" { Category::A } / "s" { Category::S }
+        rule category() -> Category = c:$(['a'..='z' | 'A'..='Z' | '_'] ['a'..='z' | 'A'..='Z' | '0'..='9' | '_']*) { c.to_owned() }
+        rule operator() -> Operator = "<=" { Operator::LessOrEqual } / "<" { Operator::LessThan } / ">=" { Operator::GreaterOrEqual } / ">" { Operator::GreaterThan } / "==" { Operator::Equal } / "!=" { Operator::NotEqual }
         rule value() -> usize = n:$(['0'..='9']+) {? n.parse().or(Err("usize")) }
-        rule greaterThan() -> Rule = category:category() ">" value:value() ":" result:ruleResult() { Rule { condition: RuleCondition::GreaterThan { category, value }, result } }
-        rule lessThan() -> Rule = category:category() "<" value:value() ":" result:ruleResult() { Rule { condition: RuleCondition::LessThan { category, value }, result } }
+        rule comparison() -> Rule = category:category() operator:operator() value:value() ":" result:ruleResult() { Rule { condition: RuleCondition::Compare { category, operator, value }, result } }
         rule default() -> Rule = result:ruleResult() { Rule { condition: RuleCondition::Default, result } }
-        rule workflowRule() -> Rule = greaterThan() / lessThan() / default()
-        rule rules() -> Vec<Rule> = (workflowRule() ** ",")
+        // The default rule is always the last one (any rule after it would never apply)
+        rule rules() -> Vec<Rule> = c:(r:comparison() "," { r })* d:default() { c.into_iter().chain([d]).collect() }
         rule workflow() -> (String, Vec<Rule>) = label:label() "{" rules:rules() "}" { ( label, rules ) }
         pub rule workflows() -> Workflows = w:(workflow() ** "\n") { w.into_iter().collect() }
 
@@ -167,24 +109,139 @@ peg::parser! {
     }
 }
 
-impl RatingRange {
-    fn len(&self) -> usize {
-        self.1 - self.0 + 1
+/// Print the workflows in the same format as the input ("in" first, then sorted by label)
+fn format_workflows(workflows: &Workflows) -> String {
+    workflows
+        .iter()
+        .sorted_by_key(|&(label, _)| (label != "in", label))
+        .map(|(label, rules)| format!("{}{{{}}}", label, rules.iter().join(",")))
+        .join("\n")
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.condition {
+            RuleCondition::Compare { category, operator, value } => {
+                write!(f, "{}{}{}:{}", category, operator, value, self.result)
+            }
+            RuleCondition::Default => write!(f, "{}", self.result),
+        }
+    }
+}
+
+impl fmt::Display for RuleResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleResult::Accepted => write!(f, "A"),
+            RuleResult::Rejected => write!(f, "R"),
+            RuleResult::ChainTo(label) => write!(f, "{}", label),
+        }
     }
 }
-#[derive(Copy, Clone, Debug)]
-struct RatingRange(usize, usize); // Inclusive
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self {
+            Operator::LessThan => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::GreaterThan => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+/// Lowest and highest (excluded) ratings of each range of the set, `None` if it is unbounded
+fn bounded_ranges(ratings: &RatingSet) -> Option<Vec<(usize, usize)>> {
+    ratings
+        .iter()
+        .map(|r| match (r.start_bound(), r.end_bound()) {
+            (Bound::Included(&&start), Bound::Excluded(&&end)) => Some((start, end)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Number of values in a bounded set of ratings
+fn ratings_count(ratings: &RatingSet) -> usize {
+    bounded_ranges(ratings)
+        .expect("unbounded ratings, although the domains are checked by PartsRange::new")
+        .into_iter()
+        .map(|(start, end)| end - start)
+        .sum()
+}
 
 impl PartsRange {
-    fn empty() -> PartsRange {
-        PartsRange(HashMap::new())
+    /// All the parts with ratings within the domains. Each domain must be bounded, so that its
+    /// ratings can be counted, and each category compared by the workflows must have one.
+    fn new(domains: &Domains, workflows: &Workflows) -> Result<PartsRange, CategoryError> {
+        if let Some(category) = domains
+            .iter()
+            .find_map(|(category, ratings)| bounded_ranges(ratings).is_none().then_some(category))
+        {
+            return Err(CategoryError::UnboundedDomain(category.clone()));
+        }
+        for rule in workflows.values().flatten() {
+            if let RuleCondition::Compare { category, .. } = &rule.condition {
+                if !domains.contains_key(category) {
+                    return Err(CategoryError::NoDomain(category.clone()));
+                }
+            }
+        }
+        Ok(PartsRange(domains.clone()))
     }
+
+    fn is_empty(&self) -> bool {
+        self.0.values().any(|r| r.is_empty())
+    }
+
+    /// Same categories, without any rating left
+    fn emptied(&self) -> PartsRange {
+        PartsRange(
+            self.0
+                .keys()
+                .map(|category| (category.clone(), RangeSet::empty()))
+                .collect(),
+        )
+    }
+
     fn size(&self) -> usize {
-        self.0.values().map(|r| r.len()).product()
+        self.0.values().map(ratings_count).product()
+    }
+
+    /// Split into the parts which match the condition, and those which don't
+    fn split(&self, condition: &RuleCondition) -> (PartsRange, PartsRange) {
+        match condition {
+            RuleCondition::Compare { category, .. } => {
+                let ratings = self
+                    .0
+                    .get(category)
+                    .expect("no domain, although the categories are checked by PartsRange::new");
+                let matching_ratings = condition.matching_ratings();
+
+                let mut matched = self.clone();
+                matched
+                    .0
+                    .insert(category.clone(), ratings.intersection(&matching_ratings));
+                let mut not_matched = self.clone();
+                not_matched
+                    .0
+                    .insert(category.clone(), ratings.difference(&matching_ratings));
+                (matched, not_matched)
+            }
+            RuleCondition::Default => (self.clone(), self.emptied()),
+        }
     }
 }
 #[derive(Clone, Debug)]
-struct PartsRange(HashMap<Category, RatingRange>);
+struct PartsRange(Domains);
+
+type RatingSet = RangeSet2<usize>;
+
+/// Possible ratings for each category
+type Domains = HashMap<Category, RatingSet>;
 
 type Workflows = HashMap<String, Vec<Rule>>;
 
@@ -194,13 +251,70 @@ struct Rule {
     result: RuleResult,
 }
 
-#[derive(Eq, PartialEq, Debug)]
+impl RuleCondition {
+    /// An error if the part has no rating for the compared category
+    fn matches(&self, part: &Part) -> Result<bool, CategoryError> {
+        Ok(match self {
+            RuleCondition::Compare { category, operator, value } => {
+                let rating = part
+                    .get_rating(category)
+                    .ok_or_else(|| CategoryError::NoRating(category.clone()))?;
+                match operator {
+                    Operator::LessThan => rating < *value,
+                    Operator::LessOrEqual => rating <= *value,
+                    Operator::GreaterThan => rating > *value,
+                    Operator::GreaterOrEqual => rating >= *value,
+                    Operator::Equal => rating == *value,
+                    Operator::NotEqual => rating != *value,
+                }
+            }
+            RuleCondition::Default => true,
+        })
+    }
+
+    /// All the ratings (for the condition's category) which match this condition
+    fn matching_ratings(&self) -> RatingSet {
+        match *self {
+            RuleCondition::Compare { operator, value, .. } => {
+                // `None` if `value` is the greatest possible rating
+                let next = value.checked_add(1);
+                let equal = || next.map_or(RangeSet::from(value..), |n| RangeSet::from(value..n));
+                match operator {
+                    Operator::LessThan => RangeSet::from(..value),
+                    Operator::LessOrEqual => next.map_or(RangeSet::all(), |n| RangeSet::from(..n)),
+                    Operator::GreaterThan => {
+                        next.map_or(RangeSet::empty(), |n| RangeSet::from(n..))
+                    }
+                    Operator::GreaterOrEqual => RangeSet::from(value..),
+                    Operator::Equal => equal(),
+                    Operator::NotEqual => !equal(),
+                }
+            }
+            RuleCondition::Default => RangeSet::all(),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
 enum RuleCondition {
-    GreaterThan { category: Category, value: usize },
-    LessThan { category: Category, value: usize },
+    Compare {
+        category: Category,
+        operator: Operator,
+        value: usize,
+    },
     Default,
 }
 
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+enum Operator {
+    LessThan,
+    LessOrEqual,
+    GreaterThan,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
 #[derive(Eq, PartialEq, Debug)]
 enum RuleResult {
     Accepted,
@@ -209,11 +323,8 @@ enum RuleResult {
 }
 
 impl Part {
-    fn get_rating(&self, category: &Category) -> usize {
-        match self.ratings.get(category) {
-            Some(&v) => v,
-            None => 0,
-        }
+    fn get_rating(&self, category: &str) -> Option<usize> {
+        self.ratings.get(category).copied()
     }
 }
 
@@ -222,17 +333,38 @@ struct Part {
     ratings: HashMap<Category, usize>,
 }
 
-#[derive(Hash, Eq, PartialEq, Debug, Copy, Clone)]
-enum Category {
-    X,
-    M,
-    A,
-    S,
+/// Name of a part's attribute, e.g. "x", "m", "a" or "s" in the puzzle
+type Category = String;
+
+/// A category which the rules compare, but which can't be looked up
+#[derive(Eq, PartialEq, Debug)]
+enum CategoryError {
+    /// A part has no rating for the category
+    NoRating(Category),
+    /// There is no domain for the category
+    NoDomain(Category),
+    /// The domain of the category has no lowest or no highest rating, so it can't be counted
+    UnboundedDomain(Category),
+}
+
+impl fmt::Display for CategoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CategoryError::NoRating(category) => write!(f, "part without a {} rating", category),
+            CategoryError::NoDomain(category) => write!(f, "no domain for category {}", category),
+            CategoryError::UnboundedDomain(category) => {
+                write!(f, "unbounded domain for category {}", category)
+            }
+        }
+    }
 }
 
+impl Error for CategoryError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     static TEST_PARTS: &str = "{x=787,m=2655,a=1222,s=2876}
 {x=1679,m=44,a=2067,s=496}
@@ -253,29 +385,280 @@ gd{a>3333:R,R}
 hdj{m>838:A,pv}";
 
     #[test]
-    fn test() {
-        println!("parts:");
-        for p in parse_parts(TEST_PARTS) {
-            println!("{:?}", p);
-        }
-        println!();
-        println!("workflows:");
-        for w in parse_workflows(TEST_WORKFLOWS) {
-            println!("{:?}", w);
-        }
-        println!()
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_parts", parse_parts(TEST_PARTS));
+        common::assert_snapshot!("test_workflows", parse_workflows(TEST_WORKFLOWS));
+    }
+
+    #[test]
+    fn test_format_workflows() {
+        let workflows = parse_workflows(TEST_WORKFLOWS);
+        let formatted = format_workflows(&workflows);
+        assert!(formatted.starts_with("in{s<1351:px,qqz}\n"));
+        assert_eq!(parse_workflows(&formatted), workflows);
+    }
+
+    #[test]
+    fn test_decision_tree() {
+        let workflows = parse_workflows(TEST_WORKFLOWS);
+        let decision_tree = DecisionTree::compile(&workflows);
+        let accepted = parse_parts(TEST_PARTS)
+            .iter()
+            .map(|p| decision_tree.is_accepted(p).unwrap())
+            .collect_vec();
+        assert_eq!(accepted, vec![true, false, true, false, true]);
+
+        // "gd" always rejects, and "lnx" always accepts
+        let compiled_workflows = decision_tree.to_workflows();
+        assert!(!format_workflows(&compiled_workflows).contains("a>3333"));
+        assert!(!format_workflows(&compiled_workflows).contains("m>1548"));
+        let recompiled =
+            DecisionTree::compile(&parse_workflows(&format_workflows(&compiled_workflows)));
+        assert_eq!(recompiled, decision_tree);
+        assert_eq!(
+            part_2(&compiled_workflows, &xmas_domains()),
+            Ok(167409079868000)
+        );
     }
 
     #[test]
     fn test_part_1() {
         assert_eq!(
-            part_1(&parse_parts(TEST_PARTS), &parse_workflows(&TEST_WORKFLOWS)),
-            19114
+            part_1(&parse_parts(TEST_PARTS), &parse_workflows(TEST_WORKFLOWS)),
+            Ok(19114)
         );
     }
 
     #[test]
     fn test_part_2() {
-        assert_eq!(part_2(&parse_workflows(&TEST_WORKFLOWS)), 167409079868000);
+        assert_eq!(
+            part_2(&parse_workflows(TEST_WORKFLOWS), &xmas_domains()),
+            Ok(167409079868000)
+        );
+    }
+
+    #[test]
+    fn test_generic_categories_and_operators() {
+        let workflows = parse_workflows(
+            "in{weight>=10:heavy,colour==3:A,colour!=4:R,size<=2:A,R}
+heavy{size<5:R,A}",
+        );
+        assert_eq!(
+            format_workflows(&workflows),
+            "in{weight>=10:heavy,colour==3:A,colour!=4:R,size<=2:A,R}\nheavy{size<5:R,A}"
+        );
+
+        let parts = parse_parts(
+            "{weight=10,size=5,colour=1}
+{weight=9,size=5,colour=3}
+{weight=9,size=2,colour=4}
+{weight=9,size=2,colour=5}
+{weight=12,size=4,colour=3}",
+        );
+        let accepted = parts
+            .iter()
+            .map(|p| DecisionTree::compile(&workflows).is_accepted(p).unwrap())
+            .collect_vec();
+        assert_eq!(accepted, vec![true, true, true, false, false]);
+
+        let domains = hashmap! {
+            "weight".to_owned() => RangeSet::from(0..20),
+            "size".to_owned() => RangeSet::from(1..11),
+            "colour".to_owned() => RangeSet::from(1..6),
+        };
+        // weight >= 10 and size >= 5: 10 * 6 * 5
+        // weight < 10 and colour == 3: 10 * 10 * 1
+        // weight < 10 and colour == 4 and size <= 2: 10 * 2 * 1
+        assert_eq!(part_2(&workflows, &domains), Ok(300 + 100 + 20));
+    }
+
+    #[test]
+    fn test_extreme_values() {
+        let workflows = parse_workflows(&format!("in{{x<={}:A,R}}", usize::MAX));
+        let domains = hashmap! { "x".to_owned() => RangeSet::from(1..11) };
+        assert_eq!(part_2(&workflows, &domains), Ok(10));
+
+        let workflows = parse_workflows(&format!("in{{x>{}:A,x=={}:A,R}}", usize::MAX, usize::MAX));
+        assert_eq!(part_2(&workflows, &domains), Ok(0));
+        let part = Part { ratings: hashmap! { "x".to_owned() => usize::MAX } };
+        assert!(DecisionTree::compile(&workflows)
+            .is_accepted(&part)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_category_errors() {
+        let workflows = parse_workflows("in{x<5:A,colour>2:R,A}");
+        // A typo is an error, instead of a rating of 0, as soon as a rule compares it
+        assert_eq!(
+            part_1(&parse_parts("{x=7,color=3}"), &workflows),
+            Err(CategoryError::NoRating("colour".to_owned()))
+        );
+        assert_eq!(part_1(&parse_parts("{x=3,color=3}"), &workflows), Ok(6));
+
+        let domains = hashmap! { "x".to_owned() => RangeSet::from(1..11) };
+        assert_eq!(
+            part_2(&workflows, &domains),
+            Err(CategoryError::NoDomain("colour".to_owned()))
+        );
+        for x_ratings in [RangeSet::from(1..), RangeSet::all()] {
+            let domains = hashmap! {
+                "x".to_owned() => x_ratings,
+                "colour".to_owned() => RangeSet::from(1..6),
+            };
+            assert_eq!(
+                part_2(&workflows, &domains),
+                Err(CategoryError::UnboundedDomain("x".to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_rule_is_last() {
+        assert!(workflow_parser::workflows("in{A,x<5:R}").is_err());
+        assert!(workflow_parser::workflows("in{x<5:R}").is_err());
+        assert_eq!(parse_workflows("in{A}")["in"].len(), 1);
+    }
+
+    /// Follow the workflows one rule at a time
+    fn is_accepted_naive(workflows: &Workflows, part: &Part) -> bool {
+        let mut label = "in";
+        loop {
+            let rule = workflows[label]
+                .iter()
+                .find(|rule| rule.condition.matches(part).unwrap())
+                .expect("no default rule");
+            match &rule.result {
+                RuleResult::Accepted => return true,
+                RuleResult::Rejected => return false,
+                RuleResult::ChainTo(next) => label = next,
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_workflows() {
+        use common::gen::workflows::{self as gen, WorkflowParams};
+        let mut rng = common::gen::rng(19);
+        for depth in [1, 4, 8] {
+            let params = WorkflowParams { depth, ..WorkflowParams::default() };
+            let workflows = parse_workflows(&gen::workflows(&mut rng, params));
+            let parts = parse_parts(&gen::parts(&mut rng, 200));
+            assert_eq!(parse_workflows(&format_workflows(&workflows)), workflows);
+
+            let accepted_ratings: usize = parts
+                .iter()
+                .filter(|p| is_accepted_naive(&workflows, p))
+                .map(|p| p.ratings.values().sum::<usize>())
+                .sum();
+            assert_eq!(part_1(&parts, &workflows), Ok(accepted_ratings));
+
+            let compiled_workflows = DecisionTree::compile(&workflows).to_workflows();
+            assert_eq!(
+                part_2(&compiled_workflows, &xmas_domains()),
+                part_2(&workflows, &xmas_domains())
+            );
+        }
+    }
+
+    const CATEGORIES: [&str; 3] = ["x", "m", "a"];
+    const MAX_RATING: usize = 6;
+
+    fn small_domains() -> Domains {
+        CATEGORIES
+            .iter()
+            .map(|&c| (c.to_owned(), RangeSet::from(1..MAX_RATING + 1)))
+            .collect()
+    }
+
+    fn label(idx: usize) -> String {
+        if idx == 0 {
+            "in".to_owned()
+        } else {
+            format!("w{}", (b'a' + idx as u8) as char)
+        }
+    }
+
+    /// Up to 6 workflows, where each workflow can only chain to the following ones (so that there
+    /// is no cycle), with thresholds around the small domains
+    fn workflows() -> impl Strategy<Value = Workflows> {
+        let operator = prop::sample::select(vec![
+            Operator::LessThan,
+            Operator::LessOrEqual,
+            Operator::GreaterThan,
+            Operator::GreaterOrEqual,
+            Operator::Equal,
+            Operator::NotEqual,
+        ]);
+        // 0: accepted, 1: rejected, 2: chain to one of the following workflows (if any)
+        let result = (0..3usize, any::<prop::sample::Index>());
+        let condition = (
+            prop::sample::select(CATEGORIES.to_vec()),
+            operator,
+            0..=MAX_RATING + 1,
+        );
+        let rules = (
+            prop::collection::vec((condition, result.clone()), 0..=3),
+            result,
+        );
+        prop::collection::vec(rules, 1..=6).prop_map(|workflows| {
+            let count = workflows.len();
+            let to_result = |idx: usize, (kind, target): (usize, prop::sample::Index)| match kind {
+                2 if idx + 1 < count => {
+                    RuleResult::ChainTo(label(idx + 1 + target.index(count - idx - 1)))
+                }
+                0 | 2 => RuleResult::Accepted,
+                _ => RuleResult::Rejected,
+            };
+            workflows
+                .into_iter()
+                .enumerate()
+                .map(|(idx, (rules, default))| {
+                    let mut rules = rules
+                        .into_iter()
+                        .map(|((category, operator, value), result)| Rule {
+                            condition: RuleCondition::Compare {
+                                category: category.to_owned(),
+                                operator,
+                                value,
+                            },
+                            result: to_result(idx, result),
+                        })
+                        .collect_vec();
+                    rules.push(Rule {
+                        condition: RuleCondition::Default,
+                        result: to_result(idx, default),
+                    });
+                    (label(idx), rules)
+                })
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn decision_tree_matches_naive(
+            workflows in workflows(),
+            ratings in prop::collection::vec(prop::collection::vec(1..=MAX_RATING, CATEGORIES.len()), 1..20)
+        ) {
+            let decision_tree = DecisionTree::compile(&workflows);
+            for ratings in ratings {
+                let part = Part { ratings: CATEGORIES.iter().map(|c| c.to_string()).zip(ratings).collect() };
+                prop_assert_eq!(decision_tree.is_accepted(&part).unwrap(), is_accepted_naive(&workflows, &part));
+            }
+        }
+
+        #[test]
+        fn parts_range_count_matches_naive(workflows in workflows()) {
+            let accepted = (0..CATEGORIES.len())
+                .map(|_| 1..=MAX_RATING)
+                .multi_cartesian_product()
+                .filter(|ratings| {
+                    let part = Part { ratings: CATEGORIES.iter().map(|c| c.to_string()).zip(ratings.clone()).collect() };
+                    is_accepted_naive(&workflows, &part)
+                })
+                .count();
+            prop_assert_eq!(part_2(&workflows, &small_domains()), Ok(accepted));
+        }
     }
 }
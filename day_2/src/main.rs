@@ -1,80 +1,94 @@
-use common::itertools::Itertools;
+use common::Solution;
 use sscanf::sscanf;
+use std::collections::BTreeMap;
 
 static INPUT: &str = include_str!("input");
 
+const PUZZLE_COLOURS: [&str; 3] = ["red", "green", "blue"];
+
+struct Day2;
+
+impl Solution for Day2 {}
+
 fn main() {
-    let games = parse_input(INPUT);
+    let games = parse_input(INPUT, Some(&PUZZLE_COLOURS)).unwrap();
 
     let res1 = part_1(&games);
-    println!("Part 1 result: {}", res1);
+    Day2::print_answer(1, res1);
 
     let res2 = part_2(&games);
-    println!("Part 2 result: {}", res2);
+    Day2::print_answer(2, res2);
 }
 
 fn part_1(games: &[Game]) -> usize {
+    let bag = SetOfCubes::from([("red", 12), ("green", 13), ("blue", 14)]);
+    feasible_games(games, &bag).iter().map(|game| game.id).sum()
+}
+
+fn part_2(games: &[Game]) -> usize {
     games
         .iter()
-        .filter_map(|game| {
-            let possible = game
-                .draws
-                .iter()
-                .all(|draw| draw.red <= 12 && draw.green <= 13 && draw.blue <= 14);
-            if possible {
-                Some(game.id)
-            } else {
-                None
-            }
-        })
+        .map(|game| game.minimal_bag().power(&PUZZLE_COLOURS))
         .sum()
 }
 
-fn part_2(games: &[Game]) -> usize {
+/// Games which could have been played with this bag
+fn feasible_games<'a>(games: &'a [Game], bag: &SetOfCubes) -> Vec<&'a Game> {
     games
         .iter()
-        .map(|game| {
-            // Construct the minimum set for this game
-            let red = game.draws.iter().map(|d| d.red).max().unwrap_or_else(|| 0);
-            let green = game
-                .draws
-                .iter()
-                .map(|d| d.green)
-                .max()
-                .unwrap_or_else(|| 0);
-            let blue = game.draws.iter().map(|d| d.blue).max().unwrap_or_else(|| 0);
-            red * green * blue
-        })
-        .sum()
+        .filter(|game| game.draws.iter().all(|draw| bag.contains(draw)))
+        .collect()
 }
 
-fn parse_input(input: &str) -> Vec<Game> {
+/// If `known_colours` is given, any other colour is an error
+fn parse_input(input: &str, known_colours: Option<&[&str]>) -> Result<Vec<Game>, ParseError> {
     input
-        .split("\n")
+        .lines()
         .map(|line| {
-            let (id, draws_str) = sscanf!(line, "Game {usize}: {str}").unwrap();
+            let invalid_syntax = || ParseError::InvalidSyntax(line.to_owned());
+            let (id, draws_str) =
+                sscanf!(line, "Game {usize}: {str}").map_err(|_| invalid_syntax())?;
             let draws = draws_str
                 .split("; ")
                 .map(|draw_str| {
                     let mut draw = SetOfCubes::empty();
-                    draw_str.split(", ").for_each(|color_draw| {
-                        let (value, color) = sscanf!(color_draw, "{usize} {str}").unwrap();
-                        if color == "red" {
-                            draw.red = value;
-                        } else if color == "green" {
-                            draw.green = value;
-                        } else if color == "blue" {
-                            draw.blue = value;
+                    for colour_draw in draw_str.split(", ") {
+                        let (value, colour) =
+                            sscanf!(colour_draw, "{usize} {str}").map_err(|_| invalid_syntax())?;
+                        if known_colours.is_some_and(|colours| !colours.contains(&colour)) {
+                            return Err(ParseError::UnknownColour {
+                                game_id: id,
+                                colour: colour.to_owned(),
+                            });
                         }
-                    });
-                    draw
+                        *draw.0.entry(colour.to_owned()).or_default() += value;
+                    }
+                    Ok(draw)
                 })
-                .collect_vec();
-            Game { id, draws }
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Game { id, draws })
         })
         .collect()
 }
 
+#[derive(PartialEq, Eq, Debug)]
+enum ParseError {
+    InvalidSyntax(String),
+    UnknownColour { game_id: usize, colour: String },
+}
+
+impl Game {
+    /// The smallest bag with which this game could have been played
+    fn minimal_bag(&self) -> SetOfCubes {
+        let mut bag = SetOfCubes::empty();
+        for (colour, &value) in self.draws.iter().flat_map(|draw| draw.0.iter()) {
+            let count = bag.0.entry(colour.clone()).or_default();
+            *count = value.max(*count);
+        }
+        bag
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 struct Game {
     id: usize,
@@ -83,20 +97,46 @@ struct Game {
 
 impl SetOfCubes {
     fn empty() -> SetOfCubes {
-        SetOfCubes { red: 0, green: 0, blue: 0 }
+        SetOfCubes(BTreeMap::new())
+    }
+
+    fn get(&self, colour: &str) -> usize {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+
+    /// Whether `other` can be drawn from this set
+    fn contains(&self, other: &SetOfCubes) -> bool {
+        other
+            .0
+            .iter()
+            .all(|(colour, &value)| value <= self.get(colour))
+    }
+
+    /// Product of the numbers of cubes of each of these colours
+    fn power(&self, colours: &[&str]) -> usize {
+        colours.iter().map(|colour| self.get(colour)).product()
     }
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
-struct SetOfCubes {
-    red: usize,
-    green: usize,
-    blue: usize,
+impl<const N: usize> From<[(&str, usize); N]> for SetOfCubes {
+    fn from(cubes: [(&str, usize); N]) -> SetOfCubes {
+        SetOfCubes(
+            cubes
+                .into_iter()
+                .map(|(colour, value)| (colour.to_owned(), value))
+                .collect(),
+        )
+    }
 }
 
+/// Number of cubes for each colour
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct SetOfCubes(BTreeMap<String, usize>);
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::itertools::Itertools;
 
     static TEST_GAMES: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
 Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
@@ -106,59 +146,120 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
 
     #[test]
     fn test_part_1() {
-        assert_eq!(part_1(&parse_input(TEST_GAMES)), 8);
+        assert_eq!(
+            part_1(&parse_input(TEST_GAMES, Some(&PUZZLE_COLOURS)).unwrap()),
+            8
+        );
     }
 
     #[test]
     fn test_part_2() {
-        assert_eq!(part_2(&parse_input(TEST_GAMES)), 2286);
+        assert_eq!(
+            part_2(&parse_input(TEST_GAMES, Some(&PUZZLE_COLOURS)).unwrap()),
+            2286
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_input("Game 1: 3 blue; 2 purple", Some(&PUZZLE_COLOURS)),
+            Err(ParseError::UnknownColour { game_id: 1, colour: "purple".to_owned() })
+        );
+        assert_eq!(
+            parse_input("Game 1: 3 blue, red", Some(&PUZZLE_COLOURS)),
+            Err(ParseError::InvalidSyntax("Game 1: 3 blue, red".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_queries() {
+        let games = parse_input(
+            "Game 1: 3 blue, 2 purple; 1 yellow
+Game 2: 1 purple, 5 blue; 4 yellow, 1 blue
+Game 3: 2 yellow",
+            None,
+        )
+        .unwrap();
+        let feasible_ids = |bag: SetOfCubes| {
+            feasible_games(&games, &bag)
+                .iter()
+                .map(|game| game.id)
+                .collect_vec()
+        };
+        assert_eq!(
+            feasible_ids(SetOfCubes::from([
+                ("blue", 3),
+                ("purple", 2),
+                ("yellow", 2)
+            ])),
+            vec![1, 3]
+        );
+        assert_eq!(feasible_ids(SetOfCubes::from([("yellow", 4)])), vec![3]);
+        assert_eq!(feasible_ids(SetOfCubes::empty()), Vec::<usize>::new());
+
+        let minimal_bag = games[1].minimal_bag();
+        assert_eq!(
+            minimal_bag,
+            SetOfCubes::from([("blue", 5), ("purple", 1), ("yellow", 4)])
+        );
+        assert_eq!(minimal_bag.power(&["blue", "purple", "yellow"]), 20);
+        assert_eq!(minimal_bag.power(&["blue", "red"]), 0);
     }
 
     #[test]
     fn test_parse_input() {
         assert_eq!(
-            parse_input(TEST_GAMES),
+            parse_input(TEST_GAMES, Some(&PUZZLE_COLOURS)).unwrap(),
             vec![
                 Game {
                     id: 1,
                     draws: vec![
-                        SetOfCubes { red: 4, green: 0, blue: 3 },
-                        SetOfCubes { red: 1, green: 2, blue: 6 },
-                        SetOfCubes { red: 0, green: 2, blue: 0 }
+                        SetOfCubes::from([("red", 4), ("blue", 3)]),
+                        SetOfCubes::from([("red", 1), ("green", 2), ("blue", 6)]),
+                        SetOfCubes::from([("green", 2)])
                     ]
                 },
                 Game {
                     id: 2,
                     draws: vec![
-                        SetOfCubes { red: 0, green: 2, blue: 1 },
-                        SetOfCubes { red: 1, green: 3, blue: 4 },
-                        SetOfCubes { red: 0, green: 1, blue: 1 }
+                        SetOfCubes::from([("green", 2), ("blue", 1)]),
+                        SetOfCubes::from([("red", 1), ("green", 3), ("blue", 4)]),
+                        SetOfCubes::from([("green", 1), ("blue", 1)])
                     ]
                 },
                 Game {
                     id: 3,
                     draws: vec![
-                        SetOfCubes { red: 20, green: 8, blue: 6 },
-                        SetOfCubes { red: 4, green: 13, blue: 5 },
-                        SetOfCubes { red: 1, green: 5, blue: 0 }
+                        SetOfCubes::from([("red", 20), ("green", 8), ("blue", 6)]),
+                        SetOfCubes::from([("red", 4), ("green", 13), ("blue", 5)]),
+                        SetOfCubes::from([("red", 1), ("green", 5)])
                     ]
                 },
                 Game {
                     id: 4,
                     draws: vec![
-                        SetOfCubes { red: 3, green: 1, blue: 6 },
-                        SetOfCubes { red: 6, green: 3, blue: 0 },
-                        SetOfCubes { red: 14, green: 3, blue: 15 }
+                        SetOfCubes::from([("red", 3), ("green", 1), ("blue", 6)]),
+                        SetOfCubes::from([("red", 6), ("green", 3)]),
+                        SetOfCubes::from([("red", 14), ("green", 3), ("blue", 15)])
                     ]
                 },
                 Game {
                     id: 5,
                     draws: vec![
-                        SetOfCubes { red: 6, green: 3, blue: 1 },
-                        SetOfCubes { red: 1, green: 2, blue: 2 }
+                        SetOfCubes::from([("red", 6), ("green", 3), ("blue", 1)]),
+                        SetOfCubes::from([("red", 1), ("green", 2), ("blue", 2)])
                     ]
                 },
             ]
         );
     }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!(
+            "test_games",
+            parse_input(TEST_GAMES, Some(&PUZZLE_COLOURS)).unwrap()
+        );
+    }
 }
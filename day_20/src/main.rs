@@ -1,16 +1,22 @@
 use common::itertools::Itertools;
+use common::lcmx::lcmx;
+use common::Solution;
 use std::collections::{HashMap, VecDeque};
 
 static INPUT: &str = include_str!("input");
 
+struct Day20;
+
+impl Solution for Day20 {}
+
 fn main() {
     let init_system = parse_input(INPUT);
 
     let res1 = part_1(&init_system);
-    println!("Part 1 result: {}", res1);
+    Day20::print_answer(1, res1);
 
     let res2 = part_2(&init_system);
-    println!("Part 2 result: {}", res2);
+    Day20::print_answer(2, res2);
 }
 
 fn part_1(init_system: &System) -> usize {
@@ -80,11 +86,18 @@ fn part_2(init_system: &System) -> usize {
         });
     }
 
-    // the four modules which feed into nc, which itself feeds into rx
-    let mut lk_count = 0;
-    let mut fn_count = 0;
-    let mut fh_count = 0;
-    let mut hh_count = 0;
+    // The conjunction which feeds rx only sends it a low pulse once all its inputs last sent it a
+    // high pulse: each of them does so periodically, so we only need their first high pulse
+    let (feeder, feeder_inputs) = system
+        .iter()
+        .find_map(|(label, module)| match module {
+            Module::Conjunction { targets, last_received } if targets.iter().any(|t| t == "rx") => {
+                Some((label.clone(), last_received.len()))
+            }
+            _ => None,
+        })
+        .expect("no conjunction sends pulses to rx");
+    let mut first_high_pulses = HashMap::new();
 
     loop {
         button_count += 1;
@@ -95,27 +108,14 @@ fn part_2(init_system: &System) -> usize {
             Pulse::Low,
         );
         while let Some(Command { from, to, pulse }) = cmd_queue.pop_back() {
-            // Note: this works because the four counts found are prime. Otherwise, we'd need to fin the lcm.
-            if to == "nc" && pulse == Pulse::High {
-                println!(
-                    "nc received high pulse from {} on count {}",
-                    from, button_count
-                );
-                if from == "lk" {
-                    lk_count = button_count;
-                }
-                if from == "fn" {
-                    fn_count = button_count;
-                }
-                if from == "fh" {
-                    fh_count = button_count;
-                }
-                if from == "hh" {
-                    hh_count = button_count;
-                }
+            if to == feeder && pulse == Pulse::High {
+                first_high_pulses
+                    .entry(from.clone())
+                    .or_insert(button_count);
             }
-            if lk_count != 0 && fn_count != 0 && fh_count != 0 && hh_count != 0 {
-                return lk_count * fn_count * fh_count * hh_count;
+            if first_high_pulses.len() == feeder_inputs {
+                let presses = first_high_pulses.values().map(|&count| count as u64);
+                return lcmx(&presses.collect_vec()).unwrap() as usize;
             }
             if let Some(module) = system.get_mut(&to) {
                 match module {
@@ -241,4 +241,25 @@ mod tests {
     fn test_part_1_input_2() {
         assert_eq!(part_1(&parse_input(TEST_INPUT_2)), 11687500);
     }
+
+    #[test]
+    fn test_generated_networks() {
+        let mut rng = common::gen::rng(20);
+        for (counters, bits) in [(1, 2), (3, 5), (4, 12)] {
+            let network = common::gen::modules::module_network(&mut rng, counters, bits);
+            let system = parse_input(&network.input);
+            assert_eq!(system.len(), network.input.lines().count());
+            assert_eq!(
+                part_2(&system) as u64,
+                network.presses_until_rx,
+                "{}",
+                network.input
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input_2", parse_input(TEST_INPUT_2));
+    }
 }
@@ -0,0 +1,182 @@
+use crate::{part_1, DistanceCounts, Garden, Point, Topology};
+use std::fmt;
+
+/// Number of plots reachable on the infinite (repeated) garden.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Reachable {
+    Exact(u64),
+    /// The garden doesn't have the structure needed by the exact solver, so the count was
+    /// extrapolated from smaller step counts, assuming that it grows quadratically.
+    Extrapolated {
+        count: u64,
+        reason: Precondition,
+    },
+}
+
+impl fmt::Display for Reachable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reachable::Exact(count) => write!(f, "{}", count),
+            Reachable::Extrapolated { count, reason } => {
+                write!(f, "{} (extrapolated: {})", count, reason)
+            }
+        }
+    }
+}
+
+/// Structural properties which make the exact solver valid: with a clear border and a clear
+/// row and column through the start, the shortest path to any plot of another tile goes through
+/// the middle of an edge (or a corner) of that tile, at a Manhattan distance from the start.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Precondition {
+    Square,
+    OddSize,
+    CentredStart,
+    ClearStartRowAndColumn,
+    ClearBorder,
+}
+
+impl fmt::Display for Precondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Precondition::Square => "the garden is not square",
+            Precondition::OddSize => "the garden size is not odd",
+            Precondition::CentredStart => "the start is not in the centre",
+            Precondition::ClearStartRowAndColumn => "the start row or column has rocks",
+            Precondition::ClearBorder => "the border has rocks",
+        };
+        f.write_str(description)
+    }
+}
+
+pub fn count_reachable(garden: &Garden, steps: usize) -> Reachable {
+    match check_preconditions(garden) {
+        Ok(()) => Reachable::Exact(count_exact(garden, steps)),
+        Err(reason) => Reachable::Extrapolated { count: extrapolate(garden, steps), reason },
+    }
+}
+
+/// The first precondition which doesn't hold, if any.
+pub fn check_preconditions(garden: &Garden) -> Result<(), Precondition> {
+    let n = garden.size;
+    let open = |x: usize, y: usize| garden.open_plots[y][x];
+    let centre = (n / 2) as i64;
+    if garden.open_plots.iter().any(|row| row.len() != n) {
+        Err(Precondition::Square)
+    } else if n.is_multiple_of(2) {
+        Err(Precondition::OddSize)
+    } else if garden.start_plot != (Point { x: centre, y: centre }) {
+        Err(Precondition::CentredStart)
+    } else if !(0..n).all(|i| open(i, n / 2) && open(n / 2, i)) {
+        Err(Precondition::ClearStartRowAndColumn)
+    } else if !(0..n).all(|i| open(i, 0) && open(i, n - 1) && open(0, i) && open(n - 1, i)) {
+        Err(Precondition::ClearBorder)
+    } else {
+        Ok(())
+    }
+}
+
+/// Every tile is entered from the point closest to the start: the middle of an edge for the tiles
+/// in line with the start tile, or a corner for the tiles of each quadrant. Its plots are then at
+/// the tile's entry distance plus their distance from that entry point within the tile.
+fn count_exact(garden: &Garden, steps: usize) -> u64 {
+    let n = garden.size;
+    let c = n / 2;
+    let last = n - 1;
+    let point = |x: usize, y: usize| Point { x: x as i64, y: y as i64 };
+
+    let mut count =
+        DistanceCounts::new(garden, point(c, c), Topology::Bounded, usize::MAX).reachable(steps);
+
+    // Tile t ≥ 1 in line with the start is entered after c + 1 + (t - 1) * n steps
+    for entry in [point(0, c), point(last, c), point(c, 0), point(c, last)] {
+        let distances = DistanceCounts::new(garden, entry, Topology::Bounded, usize::MAX);
+        count += (c + 1..=steps)
+            .step_by(n)
+            .map(|offset| distances.reachable(steps - offset))
+            .sum::<u64>();
+    }
+
+    // There are m + 1 tiles at (tx, ty) with tx + ty = m + 2 in a quadrant, entered after
+    // 2 * (c + 1) + m * n steps
+    for entry in [
+        point(0, 0),
+        point(last, 0),
+        point(0, last),
+        point(last, last),
+    ] {
+        let distances = DistanceCounts::new(garden, entry, Topology::Bounded, usize::MAX);
+        count += (2 * (c + 1)..=steps)
+            .step_by(n)
+            .enumerate()
+            .map(|(m, offset)| (m as u64 + 1) * distances.reachable(steps - offset))
+            .sum::<u64>();
+    }
+
+    count
+}
+
+/// Assumes that `f(k)`, the count after `r + k * period` steps (with `r` the remainder of the
+/// target steps), is eventually a quadratic polynomial of `k`. Its Newton forward form
+/// `f(k0) + j·Δ + j(j - 1)/2·Δ²` only involves integers, so the extrapolation is exact.
+fn extrapolate(garden: &Garden, steps: usize) -> u64 {
+    // With an odd size, tiles alternate parity, so a period of 2 tiles is more robust
+    let period = 2 * garden.size;
+    let r = steps % period;
+    let k0 = 2;
+    let target = steps / period;
+    if target < k0 + 3 {
+        return part_1(garden, steps) as u64;
+    }
+    let samples = (k0..k0 + 3)
+        .map(|k| part_1(garden, r + k * period) as i128)
+        .collect::<Vec<_>>();
+    let delta_1 = samples[1] - samples[0];
+    let delta_2 = samples[2] - 2 * samples[1] + samples[0];
+    let j = (target - k0) as i128;
+    (samples[0] + j * delta_1 + j * (j - 1) / 2 * delta_2) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+    use crate::tests::{simulate, TEST_INPUT};
+
+    /// The example garden, with the start row and column cleared
+    fn clear_garden() -> Garden {
+        let mut garden = parse_input(TEST_INPUT);
+        let c = garden.size / 2;
+        for i in 0..garden.size {
+            garden.open_plots[c][i] = true;
+            garden.open_plots[i][c] = true;
+        }
+        garden
+    }
+
+    #[test]
+    fn test_preconditions() {
+        assert_eq!(
+            check_preconditions(&parse_input(TEST_INPUT)),
+            Err(Precondition::ClearStartRowAndColumn)
+        );
+        assert_eq!(check_preconditions(&clear_garden()), Ok(()));
+
+        let mut garden = clear_garden();
+        garden.open_plots[0][3] = false;
+        assert_eq!(check_preconditions(&garden), Err(Precondition::ClearBorder));
+    }
+
+    #[test]
+    fn test_exact_against_simulation() {
+        let garden = clear_garden();
+        for steps in [0, 1, 5, 6, 7, 10, 11, 16, 17, 27, 50, 63, 100] {
+            assert_eq!(
+                count_reachable(&garden, steps),
+                Reachable::Exact(simulate(&garden, steps) as u64),
+                "{} steps",
+                steps
+            );
+        }
+    }
+}
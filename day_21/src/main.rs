@@ -1,54 +1,117 @@
+use crate::infinite::Reachable;
 use common::itertools::Itertools;
-use common::maplit::hashset;
+use common::Solution;
+
+mod infinite;
 
 static INPUT: &str = include_str!("input");
 
+struct Day21;
+
+impl Solution for Day21 {}
+
 fn main() {
     let garden = parse_input(INPUT);
 
     let res1 = part_1(&garden, 64);
-    println!("Part 1 result: {}", res1);
+    Day21::print_answer(1, res1);
 
     let res2 = part_2(&garden, 26_501_365);
-    println!("Part 2 result: {}", res2);
+    Day21::print_answer(2, res2);
 }
 
 fn part_1(garden: &Garden, steps: usize) -> usize {
-    fn accessible_neighbours(garden: &Garden, Point { x, y }: Point) -> Vec<Point> {
-        [
-            Point { x: x - 1, y },
-            Point { x: x + 1, y },
-            Point { x, y: y - 1 },
-            Point { x, y: y + 1 },
-        ].into_iter().filter(|p| garden.is_open(p)).collect_vec()
-    }
+    DistanceCounts::new(garden, garden.start_plot, Topology::Wrapping, steps).reachable(steps)
+        as usize
+}
 
-    let mut positions = hashset! {garden.start_plot};
-    for _ in 0..steps {
-        let prev_positions = positions.clone();
-        positions.clear();
-        for current_plot in prev_positions {
-            for next_plot in accessible_neighbours(garden, current_plot) {
-                positions.insert(next_plot);
-            }
-        }
-    }
-    positions.len()
+fn part_2(garden: &Garden, steps: usize) -> Reachable {
+    infinite::count_reachable(garden, steps)
 }
 
-fn part_2(garden: &Garden, steps: usize) -> f64 {
-    let y0 = part_1(garden, garden.size / 2) as f64;
-    let y1 = part_1(garden, (garden.size / 2) + garden.size) as f64;
-    let y2 = part_1(garden, (garden.size / 2) + (garden.size * 2)) as f64;
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Topology {
+    /// Only the base tile
+    Bounded,
+    /// The base tile, repeated infinitely in every direction
+    Wrapping,
+}
 
-    // Lagrange Interpolation
-    let a =  (y0 / 2.) - y1 + (y2 / 2.);
-    let b =  -3. * (y0 / 2.) + (2. * y1) - (y2 / 2.);
-    let c = y0;
+/// Distances from a point to the plots (up to some maximum distance), as cumulative counts by
+/// parity, computed with a single BFS.
+struct DistanceCounts {
+    /// `cumulative[d]`: number of plots at distance `d' ≤ d` with `d' ≡ d (mod 2)`
+    cumulative: Vec<u64>,
+}
 
-    let target = ((steps - (garden.size / 2)) / garden.size) as f64;
+impl DistanceCounts {
+    fn new(
+        garden: &Garden,
+        from: Point,
+        topology: Topology,
+        max_distance: usize,
+    ) -> DistanceCounts {
+        // Dense window containing all the plots which can be within `max_distance`
+        let (min, len) = match topology {
+            Topology::Bounded => (Point { x: 0, y: 0 }, garden.size),
+            Topology::Wrapping => {
+                let radius = max_distance as i64;
+                let min = Point { x: from.x - radius, y: from.y - radius };
+                (min, 2 * max_distance + 1)
+            }
+        };
+        let index = |Point { x, y }: Point| {
+            let (x, y) = (x - min.x, y - min.y);
+            let in_window = (0..len as i64).contains(&x) && (0..len as i64).contains(&y);
+            in_window.then(|| y as usize * len + x as usize)
+        };
+
+        let mut visited = vec![false; len * len];
+        let mut cumulative = vec![1u64];
+        visited[index(from).unwrap()] = true;
+        let mut frontier = vec![from];
+        let mut next_frontier = Vec::new();
+        while !frontier.is_empty() && cumulative.len() <= max_distance {
+            for &Point { x, y } in &frontier {
+                let neighbours = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)];
+                for (x, y) in neighbours {
+                    let p = Point { x, y };
+                    if let Some(i) = index(p) {
+                        if !visited[i] && garden.is_open(&p) {
+                            visited[i] = true;
+                            next_frontier.push(p);
+                        }
+                    }
+                }
+            }
+            std::mem::swap(&mut frontier, &mut next_frontier);
+            next_frontier.clear();
+            if !frontier.is_empty() {
+                cumulative.push(frontier.len() as u64);
+            }
+        }
+        for d in 2..cumulative.len() {
+            cumulative[d] += cumulative[d - 2];
+        }
+        DistanceCounts { cumulative }
+    }
 
-    a * target * target + b * target + c
+    /// Plots where one can be after exactly `steps` steps (by walking back and forth, one can be
+    /// on any plot at distance `d ≤ steps` with the same parity).
+    fn reachable(&self, steps: usize) -> u64 {
+        let len = self.cumulative.len();
+        if steps < len {
+            self.cumulative[steps]
+        } else {
+            // Same parity as `steps`, among the last two entries
+            let d = if (len - 1) % 2 == steps % 2 {
+                len.checked_sub(1)
+            } else {
+                len.checked_sub(2)
+            };
+            d.map_or(0, |d| self.cumulative[d])
+        }
+    }
 }
 
 fn parse_input(input: &str) -> Garden {
@@ -86,6 +149,7 @@ impl Garden {
     }
 }
 
+#[derive(Debug)]
 struct Garden {
     size: usize,
     open_plots: Vec<Vec<bool>>,
@@ -99,10 +163,11 @@ struct Point {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use common::maplit::hashset;
 
-    static TEST_INPUT: &str = "...........
+    pub static TEST_INPUT: &str = "...........
 .....###.#.
 .###.##..#.
 ..#.#...#..
@@ -114,18 +179,72 @@ mod tests {
 .##..##.##.
 ...........";
 
+    /// Reference implementation: simulate every step.
+    pub fn simulate(garden: &Garden, steps: usize) -> usize {
+        fn accessible_neighbours(garden: &Garden, Point { x, y }: Point) -> Vec<Point> {
+            [
+                Point { x: x - 1, y },
+                Point { x: x + 1, y },
+                Point { x, y: y - 1 },
+                Point { x, y: y + 1 },
+            ]
+            .into_iter()
+            .filter(|p| garden.is_open(p))
+            .collect_vec()
+        }
+
+        let mut positions = hashset! {garden.start_plot};
+        for _ in 0..steps {
+            let prev_positions = positions.clone();
+            positions.clear();
+            for current_plot in prev_positions {
+                for next_plot in accessible_neighbours(garden, current_plot) {
+                    positions.insert(next_plot);
+                }
+            }
+        }
+        positions.len()
+    }
+
     #[test]
     fn test_part_1() {
         assert_eq!(part_1(&parse_input(TEST_INPUT), 6), 16);
         assert_eq!(part_1(&parse_input(TEST_INPUT), 10), 50);
         assert_eq!(part_1(&parse_input(TEST_INPUT), 50), 1594);
-        // assert_eq!(part_1(&parse_input(TEST_INPUT), 100), 6536);
-        // assert_eq!(part_1(&parse_input(TEST_INPUT), 500), 167004);
-        // assert_eq!(part_1(&parse_input(TEST_INPUT), 1000), 668697);
+        assert_eq!(part_1(&parse_input(TEST_INPUT), 100), 6536);
+        assert_eq!(part_1(&parse_input(TEST_INPUT), 500), 167004);
+        assert_eq!(part_1(&parse_input(TEST_INPUT), 1000), 668697);
+    }
+
+    #[test]
+    fn test_distance_counts() {
+        let garden = parse_input(TEST_INPUT);
+        for steps in 0..40 {
+            assert_eq!(part_1(&garden, steps), simulate(&garden, steps));
+        }
+
+        // In the base tile only, the counts stop growing once every plot is reached
+        let bounded =
+            DistanceCounts::new(&garden, garden.start_plot, Topology::Bounded, usize::MAX);
+        assert_eq!(bounded.reachable(6), 16);
+        assert_eq!(bounded.reachable(1000) + bounded.reachable(1001), 81);
+        assert_eq!(bounded.reachable(1000), 42);
     }
 
     #[test]
     fn test_part_2() {
-        assert_eq!(part_2(&parse_input(TEST_INPUT), 5000), 16733044);
+        let garden = parse_input(TEST_INPUT);
+        assert_eq!(
+            part_2(&garden, 5000),
+            Reachable::Extrapolated {
+                count: 16733044,
+                reason: infinite::Precondition::ClearStartRowAndColumn
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_input(TEST_INPUT));
     }
 }
@@ -1,17 +1,24 @@
 use std::collections::HashSet;
 use sscanf::sscanf;
 use common::itertools::Itertools;
+use common::Solution;
 
 static INPUT: &str = include_str!("input");
 
+struct Day22;
+
+impl Solution for Day22 {
+    const STUBS: &'static [u8] = &[1, 2];
+}
+
 fn main() {
     let bricks = parse_input(INPUT);
 
     let res1 = part_1(&bricks);
-    println!("Part 1 result: {}", res1);
+    Day22::print_answer(1, res1);
 
     let res2 = part_2(&bricks);
-    println!("Part 2 result: {}", res2);
+    Day22::print_answer(2, res2);
 }
 
 fn part_1(init_bricks: &[Brick]) -> usize {
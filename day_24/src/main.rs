@@ -1,16 +1,23 @@
+use common::Solution;
 use std::error::Error;
 use std::num::ParseIntError;
 
 static INPUT: &str = include_str!("input");
 
+struct Day24;
+
+impl Solution for Day24 {
+    const STUBS: &'static [u8] = &[1, 2];
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let values = parse_input(INPUT)?;
 
     let res1 = part_1(&values);
-    println!("Part 1 result: {}", res1);
+    Day24::print_answer(1, res1);
 
     let res2 = part_2(&values);
-    println!("Part 2 result: {}", res2);
+    Day24::print_answer(2, res2);
 
     Ok(())
 }
@@ -1,92 +1,151 @@
 use common::itertools::Itertools;
-use std::collections::HashSet;
-use std::hash::Hash;
+use common::Solution;
+use std::collections::HashMap;
 
 static INPUT: &str = include_str!("input");
 
+struct Day3;
+
+impl Solution for Day3 {}
+
 fn main() {
-    let (symbols, numbers, stars) = parse_input(INPUT);
+    let report = build_report(&parse_input(INPUT));
 
-    let res1 = part_1(&symbols, &numbers);
-    println!("Part 1 result: {}", res1);
+    let res1 = part_1(&report);
+    Day3::print_answer(1, res1);
 
-    let res2 = part_2(&symbols, &numbers, &stars);
-    println!("Part 2 result: {}", res2);
+    let res2 = part_2(&report);
+    Day3::print_answer(2, res2);
 }
 
-fn part_1(symbols: &HashSet<Point>, numbers: &[Part]) -> usize {
-    valid_parts(symbols, numbers).iter().map(|p| p.number).sum()
+fn part_1(report: &Report) -> usize {
+    // Every number is a part number, unless it's isolated
+    let all_numbers: usize = report.numbers.iter().map(|n| n.part.number).sum();
+    let isolated_numbers: usize = report.isolated_numbers.iter().map(|p| p.number).sum();
+    all_numbers - isolated_numbers
 }
 
-fn part_2(symbols: &HashSet<Point>, numbers: &[Part], stars: &[Point]) -> usize {
-    let parts = valid_parts(symbols, numbers);
-    stars
-        .iter()
-        .filter_map(|star| {
-            let neighbouring_points = star.neighbours();
-            let neighbouring_parts = parts
-                .iter()
-                .filter(|part| {
-                    part.points()
-                        .iter()
-                        .any(|p| neighbouring_points.contains(p))
-                })
-                .collect_vec();
-            if neighbouring_parts.len() == 2 {
-                Some(
-                    neighbouring_parts
-                        .iter()
-                        .map(|p| p.number)
-                        .product::<usize>(),
-                )
-            } else {
-                None
-            }
-        })
-        .sum()
+fn part_2(report: &Report) -> usize {
+    report.gears.iter().map(|g| g.ratio).sum()
 }
 
-fn valid_parts(symbols: &HashSet<Point>, numbers: &[Part]) -> Vec<Part> {
-    numbers
+fn build_report(schematic: &Schematic) -> Report {
+    let mut numbers = Vec::new();
+    let mut numbers_by_symbol: HashMap<Point, Vec<Part>> = HashMap::new();
+    for &part in schematic.parts.iter() {
+        let symbols = part
+            .neighbours()
+            .into_iter()
+            .filter_map(|p| match schematic.get_cell(&p) {
+                Some(Cell::Symbol(symbol)) => Some((p, symbol)),
+                _ => None,
+            })
+            .collect_vec();
+        for &(p, _) in symbols.iter() {
+            numbers_by_symbol.entry(p).or_default().push(part);
+        }
+        numbers.push(NumberReport { part, symbols });
+    }
+
+    let gears = numbers_by_symbol
+        .into_iter()
+        .filter(|&(p, _)| schematic.get_cell(&p) == Some(Cell::Symbol('*')))
+        .filter_map(|(point, parts)| match parts[..] {
+            [p1, p2] => Some(Gear { point, parts: [p1, p2], ratio: p1.number * p2.number }),
+            _ => None,
+        })
+        .sorted_by_key(|g| (g.point.y, g.point.x))
+        .collect_vec();
+
+    let isolated_numbers = numbers
         .iter()
-        .filter(|part| part.neighbours().iter().any(|p| symbols.contains(p)))
-        .cloned()
-        .collect()
+        .filter(|n| n.symbols.is_empty())
+        .map(|n| n.part)
+        .collect_vec();
+
+    Report { numbers, gears, isolated_numbers }
 }
 
-/// Return (all symbols, possible part numbers, possible gears)
-fn parse_input(input: &str) -> (HashSet<Point>, Vec<Part>, Vec<Point>) {
-    let mut symbols = HashSet::new();
-    let mut numbers = Vec::new(); // potential parts
-    let mut stars = Vec::new(); // potential gears
+fn parse_input(input: &str) -> Schematic {
+    let mut parts = Vec::new();
+    let mut cells = Vec::new();
 
     input.lines().enumerate().for_each(|(y, line)| {
-        let mut curr_part = None;
+        let mut row = Vec::new();
+        let mut curr_part: Option<Part> = None;
         line.chars().enumerate().for_each(|(x, c)| {
             if let Some(digit) = c.to_digit(10) {
-                let mut n = curr_part.unwrap_or_else(|| Part { number: 0, x_min: x, x_max: x, y });
+                let mut n = curr_part.unwrap_or(Part { number: 0, x_min: x, x_max: x, y });
                 n.x_max = x;
                 n.number = n.number * 10 + (digit as usize);
                 curr_part = Some(n);
+                row.push(Cell::Number(parts.len()));
             } else {
                 if let Some(part) = curr_part {
-                    numbers.push(part);
+                    parts.push(part);
                     curr_part = None;
                 }
-                if c != '.' {
-                    symbols.insert(Point { x, y });
-                    if c == '*' {
-                        stars.push(Point { x, y });
-                    }
-                }
+                row.push(if c == '.' {
+                    Cell::Empty
+                } else {
+                    Cell::Symbol(c)
+                });
             }
         });
         if let Some(part) = curr_part {
-            numbers.push(part);
+            parts.push(part);
         }
+        cells.push(row);
     });
 
-    (symbols, numbers, stars)
+    Schematic { cells, parts }
+}
+
+/// Everything we know about the numbers and symbols of a schematic
+#[derive(Debug)]
+struct Report {
+    /// All the numbers, in reading order
+    numbers: Vec<NumberReport>,
+    /// All the gears, in reading order
+    gears: Vec<Gear>,
+    /// Numbers which are not adjacent to any symbol
+    isolated_numbers: Vec<Part>,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct NumberReport {
+    part: Part,
+    /// Adjacent symbols
+    symbols: Vec<(Point, char)>,
+}
+
+/// A `*` symbol adjacent to exactly 2 numbers
+#[derive(PartialEq, Eq, Debug)]
+struct Gear {
+    point: Point,
+    parts: [Part; 2],
+    ratio: usize,
+}
+
+impl Schematic {
+    fn get_cell(&self, &Point { x, y }: &Point) -> Option<Cell> {
+        self.cells.get(y).and_then(|row| row.get(x)).copied()
+    }
+}
+
+#[derive(Debug)]
+struct Schematic {
+    cells: Vec<Vec<Cell>>,
+    /// All the numbers (potential parts), in reading order
+    parts: Vec<Part>,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Cell {
+    Empty,
+    Symbol(char),
+    /// A digit of the number at this index in `Schematic::parts`
+    Number(usize),
 }
 
 impl Part {
@@ -111,12 +170,6 @@ impl Part {
         }
         neighbours
     }
-
-    fn points(&self) -> Vec<Point> {
-        (self.x_min..=self.x_max)
-            .map(|x| Point { x, y: self.y })
-            .collect()
-    }
 }
 
 #[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
@@ -127,29 +180,6 @@ struct Part {
     y: usize,
 }
 
-impl Point {
-    fn neighbours(&self) -> Vec<Point> {
-        // Note: we include neighbour points which exceed the max possible x and y values because they don't matter much
-        let mut neighbours = vec![
-            Point { x: self.x, y: self.y + 1 },
-            Point { x: self.x + 1, y: self.y },
-            Point { x: self.x + 1, y: self.y + 1 },
-        ];
-        if self.x > 0 && self.y > 0 {
-            neighbours.push(Point { x: self.x - 1, y: self.y - 1 });
-        }
-        if self.x > 0 {
-            neighbours.push(Point { x: self.x - 1, y: self.y });
-            neighbours.push(Point { x: self.x - 1, y: self.y + 1 });
-        }
-        if self.y > 0 {
-            neighbours.push(Point { x: self.x, y: self.y - 1 });
-            neighbours.push(Point { x: self.x + 1, y: self.y - 1 });
-        }
-        neighbours
-    }
-}
-
 #[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
 struct Point {
     x: usize,
@@ -173,14 +203,70 @@ mod tests {
 
     #[test]
     fn test_parse_input() {
-        let (symbols, numbers, _) = parse_input(TEST_INPUT);
-        println!("{:?}", symbols);
-        println!("{:?}", numbers);
+        let schematic = parse_input(TEST_INPUT);
+        assert_eq!(schematic.parts.len(), 10);
+        assert_eq!(
+            schematic.parts[0],
+            Part { number: 467, x_min: 0, x_max: 2, y: 0 }
+        );
+        assert_eq!(
+            schematic.get_cell(&Point { x: 3, y: 1 }),
+            Some(Cell::Symbol('*'))
+        );
+        assert_eq!(
+            schematic.get_cell(&Point { x: 2, y: 2 }),
+            Some(Cell::Number(2))
+        );
+        assert_eq!(schematic.get_cell(&Point { x: 0, y: 1 }), Some(Cell::Empty));
+        assert_eq!(schematic.get_cell(&Point { x: 10, y: 0 }), None);
+    }
+
+    #[test]
+    fn test_report() {
+        let report = build_report(&parse_input(TEST_INPUT));
+        assert_eq!(
+            report.numbers[2],
+            NumberReport {
+                part: Part { number: 35, x_min: 2, x_max: 3, y: 2 },
+                symbols: vec![(Point { x: 3, y: 1 }, '*')]
+            }
+        );
+        assert_eq!(report.numbers[6].symbols, vec![(Point { x: 5, y: 5 }, '+')]);
+        assert_eq!(
+            report
+                .isolated_numbers
+                .iter()
+                .map(|p| p.number)
+                .collect_vec(),
+            vec![114, 58]
+        );
+        assert_eq!(
+            report
+                .gears
+                .iter()
+                .map(|g| (g.point, g.parts.map(|p| p.number), g.ratio))
+                .collect_vec(),
+            vec![
+                (Point { x: 3, y: 1 }, [467, 35], 16345),
+                (Point { x: 5, y: 8 }, [755, 598], 451490),
+            ]
+        );
     }
 
     #[test]
     fn test_part_1() {
-        let (symbols, numbers, stars) = parse_input(TEST_INPUT);
-        assert_eq!(part_2(&symbols, &numbers, &stars), 467835);
+        let report = build_report(&parse_input(TEST_INPUT));
+        assert_eq!(part_1(&report), 4361);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let report = build_report(&parse_input(TEST_INPUT));
+        assert_eq!(part_2(&report), 467835);
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_input(TEST_INPUT));
     }
 }
@@ -1,18 +1,35 @@
 use common::itertools::Itertools;
+use common::Solution;
 use sscanf::sscanf;
 use std::cmp::min;
 use std::collections::HashSet;
+use std::ops::Range;
 
 static INPUT: &str = include_str!("input");
 
+struct Day4;
+
+impl Solution for Day4 {}
+
 fn main() {
     let scratchcards = parse_input(INPUT);
 
     let res1 = part_1(&scratchcards);
-    println!("Part 1 result: {}", res1);
+    Day4::print_answer(1, res1);
 
     let res2 = part_2(&scratchcards);
-    println!("Part 2 result: {}", res2);
+    Day4::print_answer(2, res2);
+
+    // e.g. `cargo run -p day_4 -- --explain`
+    if std::env::args().any(|arg| arg == "--explain") {
+        for CardCopies { id, won_copies, won_from } in explain_copies(&scratchcards) {
+            let sources = won_from
+                .iter()
+                .map(|(source_id, count)| format!("{} from card {}", count, source_id))
+                .join(", ");
+            println!("Card {}: {} copies won ({})", id, won_copies, sources);
+        }
+    }
 }
 
 fn part_1(scratchcards: &[Scratchcard]) -> usize {
@@ -20,33 +37,65 @@ fn part_1(scratchcards: &[Scratchcard]) -> usize {
 }
 
 fn part_2(scratchcards: &[Scratchcard]) -> usize {
-    let mut all_cards = scratchcards.iter().map(|c| (c, 1usize)).collect_vec();
-    let last_card_idx = all_cards.len() - 1;
-
-    for idx in 0..=last_card_idx {
-        let (card, count) = all_cards[idx];
-        let m = card.matches();
-        if m > 0 && idx < last_card_idx {
-            let idx_min = idx + 1;
-            let idx_max = min(idx + m, last_card_idx);
-            for i in idx_min..=idx_max {
-                all_cards[i].1 += count;
-            }
+    card_counts(scratchcards).iter().sum()
+}
+
+/// Number of instances of each card (the original and its copies). Each card wins copies of the
+/// next cards, which we propagate with a difference array: instead of adding the amount of copies
+/// to each of the next `m` cards, we record where this addition starts and where it stops.
+fn card_counts(scratchcards: &[Scratchcard]) -> Vec<usize> {
+    let mut copies_diff = vec![0isize; scratchcards.len() + 1];
+    let mut won_copies = 0;
+    scratchcards
+        .iter()
+        .enumerate()
+        .map(|(idx, card)| {
+            won_copies += copies_diff[idx];
+            let count = 1 + won_copies as usize;
+            let won_cards = won_cards(idx, card, scratchcards.len());
+            copies_diff[won_cards.start] += count as isize;
+            copies_diff[won_cards.end] -= count as isize;
+            count
+        })
+        .collect()
+}
+
+/// Indices of the cards which the card at `idx` wins a copy of (never past the last card)
+fn won_cards(idx: usize, card: &Scratchcard, cards_count: usize) -> Range<usize> {
+    idx + 1..min(idx + 1 + card.matches(), cards_count)
+}
+
+/// For each card, how many copies it was won, and by which cards (with the counts of
+/// `card_counts`, so that they always add up to part 2)
+fn explain_copies(scratchcards: &[Scratchcard]) -> Vec<CardCopies> {
+    let counts = card_counts(scratchcards);
+    let mut explanations = scratchcards
+        .iter()
+        .zip(&counts)
+        .map(|(card, &count)| CardCopies {
+            id: card.id,
+            won_copies: count - 1,
+            won_from: Vec::new(),
+        })
+        .collect_vec();
+    for (idx, card) in scratchcards.iter().enumerate() {
+        for won_card in won_cards(idx, card, scratchcards.len()) {
+            explanations[won_card].won_from.push((card.id, counts[idx]));
         }
     }
-
-    all_cards.iter().map(|(_, count)| count).sum()
+    explanations
 }
 
 fn parse_input(input: &str) -> Vec<Scratchcard> {
     input
         .lines()
         .map(|line| {
-            let (_, winning_nbrs_str, nbrs_you_have_str) =
-                sscanf!(line, "{str}: {str} | {str}").unwrap();
+            let (id_str, winning_nbrs_str, nbrs_you_have_str) =
+                sscanf!(line, "Card{str}: {str} | {str}").unwrap();
+            let id = id_str.trim().parse().unwrap();
             let winning_nbrs = parse_numbers(winning_nbrs_str);
             let nbrs_you_have = parse_numbers(nbrs_you_have_str);
-            Scratchcard { winning_nbrs, nbrs_you_have }
+            Scratchcard { id, winning_nbrs, nbrs_you_have }
         })
         .collect()
 }
@@ -72,8 +121,18 @@ impl Scratchcard {
     }
 }
 
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct CardCopies {
+    id: usize,
+    /// Copies won, in addition to the original card
+    won_copies: usize,
+    /// (card id, copies won from that card)
+    won_from: Vec<(usize, usize)>,
+}
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 struct Scratchcard {
+    id: usize,
     winning_nbrs: HashSet<usize>,
     nbrs_you_have: HashSet<usize>,
 }
@@ -98,4 +157,34 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
     fn test_part_2() {
         assert_eq!(part_2(&parse_input(TEST_INPUT)), 30);
     }
+
+    #[test]
+    fn test_parse_input() {
+        let scratchcards = parse_input("Card   7: 41 48 | 83 86  6\nCard  12:  1 | 1");
+        assert_eq!(scratchcards.iter().map(|c| c.id).collect_vec(), vec![7, 12]);
+        assert_eq!(scratchcards[1].matches(), 1);
+    }
+
+    #[test]
+    fn test_explain_copies() {
+        let scratchcards = parse_input(TEST_INPUT);
+        let explanations = explain_copies(&scratchcards);
+        assert_eq!(
+            explanations[3],
+            CardCopies { id: 4, won_copies: 7, won_from: vec![(1, 1), (2, 2), (3, 4)] }
+        );
+        assert_eq!(
+            explanations.iter().map(|c| c.won_copies).collect_vec(),
+            vec![0, 1, 3, 7, 13, 0]
+        );
+        assert_eq!(
+            explanations.iter().map(|c| 1 + c.won_copies).sum::<usize>(),
+            part_2(&scratchcards)
+        );
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_input(TEST_INPUT));
+    }
 }
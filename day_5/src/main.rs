@@ -1,4 +1,5 @@
 use common::itertools::Itertools;
+use common::Solution;
 use range_collections::{RangeSet, RangeSet2};
 use sscanf::sscanf;
 use std::collections::Bound;
@@ -7,15 +8,19 @@ use std::ops::RangeBounds;
 static SEEDS: &str = include_str!("seeds");
 static INPUT: &str = include_str!("input");
 
+struct Day5;
+
+impl Solution for Day5 {}
+
 fn main() {
     let seeds = parse_seeds(SEEDS);
     let maps = parse_input(INPUT);
 
     let res1 = part_1(&seeds, &maps);
-    println!("Part 1 result: {}", res1);
+    Day5::print_answer(1, res1);
 
     let res2 = part_2(&seeds, &maps);
-    println!("Part 2 result: {}", res2);
+    Day5::print_answer(2, res2);
 }
 
 fn part_1(seeds: &[i64], maps: &[GardenMap]) -> i64 {
@@ -177,4 +182,12 @@ humidity-to-location map:
             46
         );
     }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!(
+            "test_input",
+            (parse_seeds(TEST_SEEDS), parse_input(TEST_INPUT))
+        );
+    }
 }
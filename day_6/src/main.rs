@@ -1,16 +1,21 @@
 use common::time_execution;
+use common::Solution;
 
 /// array of (time, distance)
 static INPUT_1: [(u64, u64); 4] = [(56, 546), (97, 1927), (78, 1131), (75, 1139)];
 /// (time, distance)
 static INPUT_2: (u64, u64) = (56977875, 546192711311139);
 
+struct Day6;
+
+impl Solution for Day6 {}
+
 fn main() {
     let res1 = time_execution("part 1", || part_1(&INPUT_1));
-    println!("Part 1 result: {}", res1);
+    Day6::print_answer(1, res1);
 
     let res2 = time_execution("part 2", || part_2(INPUT_2));
-    println!("Part 2 result: {}", res2);
+    Day6::print_answer(2, res2);
 }
 
 fn part_1(races: &[(u64, u64)]) -> usize {
@@ -49,4 +54,14 @@ mod tests {
     fn test_part_2() {
         assert_eq!(part_2(TEST_INPUT_2), 71503);
     }
+
+    #[test]
+    fn test_count_ways_to_win_edge_cases() {
+        assert_eq!(count_ways_to_win(0, 0), 0);
+        assert_eq!(count_ways_to_win(2, 0), 1);
+        assert_eq!(count_ways_to_win(2, 1), 0);
+        // Exactly the record at the top of the curve is not a win
+        assert_eq!(count_ways_to_win(10, 25), 0);
+        assert_eq!(count_ways_to_win(10, 24), 1);
+    }
 }
@@ -151,4 +151,9 @@ QQQJA 483";
     fn test_part_1() {
         assert_eq!(part_1(TEST_INPUT), 6440);
     }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("part_1_test_input", parse_input(TEST_INPUT));
+    }
 }
@@ -156,7 +156,12 @@ QQQJA 483";
     }
 
     #[test]
-    fn test_part_1() {
+    fn test_part_2() {
         assert_eq!(part_2(TEST_INPUT), 5905);
     }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("part_2_test_input", parse_input(TEST_INPUT));
+    }
 }
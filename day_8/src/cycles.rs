@@ -0,0 +1,315 @@
+use crate::network::{Network, NodeId};
+use crate::Instruction;
+use common::lcmx::lcmx;
+
+/// Path of a single ghost: after `pre_period` steps, it loops forever over the same
+/// `cycle_length` steps, since its state is only its node and its position in the instructions.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GhostCycle {
+    pub pre_period: u64,
+    pub cycle_length: u64,
+    /// Steps (before the cycle starts) at which the ghost is on a Z node, which happen only once
+    pub transient_hits: Vec<u64>,
+    /// Steps (within the first cycle) at which the ghost is on a Z node, which happen again every
+    /// `cycle_length` steps
+    pub cyclic_hits: Vec<u64>,
+}
+
+impl GhostCycle {
+    pub fn trace(instructions: &[Instruction], network: &Network, start: NodeId) -> GhostCycle {
+        // Indexed by `node * instructions.len() + instruction_idx`
+        let mut first_visits = vec![None; network.len() * instructions.len()];
+        let mut hits = Vec::new();
+        let mut current_node = start;
+        let mut step = 0u64;
+        loop {
+            let instruction_idx = (step % instructions.len() as u64) as usize;
+            let state = current_node as usize * instructions.len() + instruction_idx;
+            if let Some(pre_period) = first_visits[state] {
+                let (transient_hits, cyclic_hits) = hits.iter().partition(|&&h| h < pre_period);
+                return GhostCycle {
+                    pre_period,
+                    cycle_length: step - pre_period,
+                    transient_hits,
+                    cyclic_hits,
+                };
+            }
+            first_visits[state] = Some(step);
+            if network.is_end(current_node) {
+                hits.push(step);
+            }
+            current_node = network.step(current_node, instructions[instruction_idx]);
+            step += 1;
+        }
+    }
+
+    pub fn is_hit(&self, step: u64) -> bool {
+        if step < self.pre_period {
+            self.transient_hits.contains(&step)
+        } else {
+            let step_in_cycle = self.pre_period + (step - self.pre_period) % self.cycle_length;
+            self.cyclic_hits.contains(&step_in_cycle)
+        }
+    }
+
+    fn first_hit(&self) -> Option<u64> {
+        self.transient_hits
+            .first()
+            .or(self.cyclic_hits.first())
+            .copied()
+    }
+
+    /// Whether the ghost is on a Z node exactly at every multiple of its first hit, which is what
+    /// the LCM shortcut assumes.
+    fn hits_at_multiples_of_first_hit(&self) -> bool {
+        let Some(first_hit) = self.first_hit() else {
+            return false;
+        };
+        let cycle_start = self.pre_period;
+        let cycle_end = self.pre_period + self.cycle_length;
+        // Before the cycle, no multiple may be missed (`first_hit` is 0 only if the start is a Z
+        // node, in which case the only multiple is 0 itself)
+        first_hit > 0
+            && (first_hit..cycle_start)
+                .step_by(first_hit as usize)
+                .eq(self.transient_hits.iter().copied())
+            && self.cycle_length.is_multiple_of(first_hit)
+            // Steps within the cycle stand for all their repetitions, so even step 0 must be a hit
+            && (cycle_start..cycle_end)
+                .filter(|step| step.is_multiple_of(first_hit))
+                .eq(self.cyclic_hits.iter().copied())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Analysis {
+    pub ghosts: Vec<GhostCycle>,
+    /// First step at which all the ghosts are on a Z node at the same time
+    pub first_common_hit: Option<u64>,
+    /// LCM of the first hits of all the ghosts
+    pub lcm_shortcut: Option<u64>,
+    /// Whether the input has the structure which guarantees that the LCM shortcut is correct
+    pub lcm_shortcut_valid: bool,
+}
+
+pub fn analyse(instructions: &[Instruction], network: &Network, starts: &[NodeId]) -> Analysis {
+    let ghosts = starts
+        .iter()
+        .map(|&start| GhostCycle::trace(instructions, network, start))
+        .collect::<Vec<_>>();
+    let first_common_hit = first_common_hit(&ghosts);
+    let first_hits = ghosts
+        .iter()
+        .map(|g| g.first_hit())
+        .collect::<Option<Vec<_>>>();
+    let lcm_shortcut = first_hits.and_then(|hits| lcmx(&hits));
+    let lcm_shortcut_valid =
+        lcm_shortcut.is_some() && ghosts.iter().all(|g| g.hits_at_multiples_of_first_hit());
+    Analysis { ghosts, first_common_hit, lcm_shortcut, lcm_shortcut_valid }
+}
+
+fn first_common_hit(ghosts: &[GhostCycle]) -> Option<u64> {
+    let max_pre_period = ghosts.iter().map(|g| g.pre_period).max().unwrap_or(0);
+
+    // Before all the ghosts are in their cycle, a common hit must be a transient hit of some ghost
+    let transient_common_hit = ghosts
+        .iter()
+        .flat_map(|g| g.transient_hits.iter().chain(g.cyclic_hits.iter()))
+        .copied()
+        .filter(|&step| step < max_pre_period && ghosts.iter().all(|g| g.is_hit(step)))
+        .min();
+    if transient_common_hit.is_some() {
+        return transient_common_hit;
+    }
+
+    // Afterwards, each ghost needs `step ≡ hit (mod cycle_length)` for one of its cyclic hits: we
+    // try every combination of those hits
+    let mut congruences = vec![(0i128, 1i128)];
+    for ghost in ghosts {
+        let cycle_length = ghost.cycle_length as i128;
+        congruences = congruences
+            .iter()
+            .flat_map(|&congruence| {
+                ghost
+                    .cyclic_hits
+                    .iter()
+                    .filter_map(move |&h| combine(congruence, (h as i128, cycle_length)))
+            })
+            .collect();
+    }
+    congruences
+        .into_iter()
+        .map(|(remainder, modulus)| {
+            // Smallest value `≡ remainder (mod modulus)` which is not before `max_pre_period`
+            let min = max_pre_period as i128;
+            let periods = ((min - remainder).max(0) + modulus - 1) / modulus;
+            (remainder + periods * modulus) as u64
+        })
+        .min()
+}
+
+/// Chinese Remainder Theorem, generalised to moduli which are not coprime: the solutions of
+/// `x ≡ r1 (mod m1)` and `x ≡ r2 (mod m2)`, as `x ≡ r (mod lcm(m1, m2))`, if there are any.
+fn combine((r1, m1): (i128, i128), (r2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let (g, p, _) = extended_gcd(m1, m2);
+    if (r2 - r1) % g != 0 {
+        return None;
+    }
+    // m1 * p ≡ g (mod m2), so x = r1 + m1 * p * (r2 - r1) / g works
+    let lcm = m1 / g * m2;
+    let k = ((r2 - r1) / g * p).rem_euclid(m2 / g);
+    Some(((r1 + m1 * k).rem_euclid(lcm), lcm))
+}
+
+/// (gcd, x, y) such that `a * x + b * y = gcd`
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::brute_force;
+    use crate::{parse_instructions, parse_nodes};
+    use common::itertools::Itertools;
+    use common::rand::rngs::StdRng;
+    use common::rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_combine() {
+        assert_eq!(combine((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(combine((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(combine((0, 4), (1, 6)), None);
+        assert_eq!(combine((0, 1), (5, 7)), Some((5, 7)));
+    }
+
+    #[test]
+    fn test_non_lcm_structure() {
+        // Ghost 1 hits Z at 2, 5, 8, ... and ghost 2 at 1, 3, 5, ...
+        let instructions = parse_instructions("L");
+        let network = Network::compile(&parse_nodes(
+            "1AA = (1XX, 1XX)
+1XX = (1ZZ, 1ZZ)
+1ZZ = (1YY, 1YY)
+1YY = (1WW, 1WW)
+1WW = (1ZZ, 1ZZ)
+2AA = (2ZZ, 2ZZ)
+2ZZ = (2AA, 2AA)
+3AA = (3BB, 3BB)
+3BB = (3ZZ, 3ZZ)
+3ZZ = (3BB, 3BB)",
+        ));
+        let node = |name: &str| network.id(name).unwrap();
+        let (n1, n2, n3) = (node("1AA"), node("2AA"), node("3AA"));
+
+        let analysis = analyse(&instructions, &network, &[n1, n2]);
+        assert_eq!(
+            analysis.ghosts[0],
+            GhostCycle {
+                pre_period: 2,
+                cycle_length: 3,
+                transient_hits: vec![],
+                cyclic_hits: vec![2]
+            }
+        );
+        assert_eq!(analysis.first_common_hit, Some(5));
+        assert_eq!(analysis.lcm_shortcut, Some(2));
+        assert!(!analysis.lcm_shortcut_valid);
+
+        // Ghost 3 hits Z at 2, 4, 6, ..., so it never meets ghost 2
+        let analysis = analyse(&instructions, &network, &[n2, n3]);
+        assert_eq!(analysis.first_common_hit, None);
+        assert!(!analysis.lcm_shortcut_valid);
+
+        let analysis = analyse(&instructions, &network, &[n1, n3]);
+        assert_eq!(analysis.first_common_hit, Some(2));
+    }
+
+    #[test]
+    fn test_transient_gap() {
+        // Ghost 1 hits Z at 2, then not before 10, 12, 14, ... and ghost 2 at 4, 8, 12, ...
+        let instructions = parse_instructions("L");
+        let network = Network::compile(&parse_nodes(
+            "1AA = (1BB, 1BB)
+1BB = (1ZZ, 1ZZ)
+1ZZ = (1CC, 1CC)
+1CC = (1DD, 1DD)
+1DD = (1EE, 1EE)
+1EE = (1FF, 1FF)
+1FF = (1GG, 1GG)
+1GG = (1HH, 1HH)
+1HH = (1II, 1II)
+1II = (1YZ, 1YZ)
+1YZ = (1JJ, 1JJ)
+1JJ = (1YZ, 1YZ)
+2AA = (2BB, 2BB)
+2BB = (2CC, 2CC)
+2CC = (2DD, 2DD)
+2DD = (2ZZ, 2ZZ)
+2ZZ = (2BB, 2BB)",
+        ));
+        let starts = [network.id("1AA").unwrap(), network.id("2AA").unwrap()];
+        let analysis = analyse(&instructions, &network, &starts);
+        assert_eq!(analysis.ghosts[0].transient_hits, vec![2]);
+        assert_eq!(analysis.lcm_shortcut, Some(4));
+        assert!(!analysis.lcm_shortcut_valid);
+        assert_eq!(analysis.first_common_hit, Some(12));
+        assert_eq!(brute_force(&network, &instructions, &starts, 100), Some(12));
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = StdRng::seed_from_u64(8);
+        for _ in 0..500 {
+            let len = rng.gen_range(2..8);
+            let suffixes = ['A', 'Z', 'X'];
+            let names = (0..len)
+                .map(|i| format!("{}{}", i, suffixes[rng.gen_range(0..3)]))
+                .collect_vec();
+            let input = names
+                .iter()
+                .map(|name| {
+                    let l = &names[rng.gen_range(0..len)];
+                    let r = &names[rng.gen_range(0..len)];
+                    format!("{} = ({}, {})", name, l, r)
+                })
+                .join("\n");
+            let network = Network::compile(&parse_nodes(&input));
+            let instructions = parse_instructions(
+                &(0..rng.gen_range(1..4))
+                    .map(|_| if rng.gen() { 'L' } else { 'R' })
+                    .collect::<String>(),
+            );
+            let starts = network.starts().collect_vec();
+
+            let analysis = analyse(&instructions, &network, &starts);
+            // Once all the ghosts are in their cycle, the whole configuration repeats
+            let max_steps = analysis
+                .ghosts
+                .iter()
+                .map(|g| g.pre_period)
+                .max()
+                .unwrap_or(0)
+                + lcmx(&analysis.ghosts.iter().map(|g| g.cycle_length).collect_vec()).unwrap_or(1);
+            assert_eq!(
+                analysis.first_common_hit,
+                brute_force(&network, &instructions, &starts, max_steps),
+                "{}\n{:?}",
+                input,
+                instructions
+            );
+            if analysis.lcm_shortcut_valid {
+                assert_eq!(
+                    analysis.lcm_shortcut, analysis.first_common_hit,
+                    "{}",
+                    input
+                );
+            }
+        }
+    }
+}
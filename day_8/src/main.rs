@@ -1,63 +1,94 @@
+use crate::network::Network;
 use common::itertools::Itertools;
-use common::lcmx::lcmx;
+use common::Solution;
 use sscanf::sscanf;
 use std::collections::HashMap;
 use std::error::Error;
-use std::iter::repeat;
+
+mod cycles;
+mod network;
 
 static INPUT_INSTRUCTIONS: &str = include_str!("instructions");
 static INPUT_NODES: &str = include_str!("nodes");
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let instructions = parse_instructions(INPUT_INSTRUCTIONS);
-    let nodes = parse_nodes(INPUT_NODES);
+/// Limit for the brute-force check of `--lcm`, which takes a couple of seconds in release
+const BRUTE_FORCE_MAX_STEPS: u64 = 100_000_000;
 
-    let res1 = part_1(&instructions, &nodes);
-    println!("Part 1 result: {}", res1);
+struct Day8;
 
-    let res2 = part_2(&instructions, &nodes);
-    println!("Part 2 result: {}", res2);
+impl Solution for Day8 {}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let instructions = parse_instructions(INPUT_INSTRUCTIONS);
+    let network = Network::compile(&parse_nodes(INPUT_NODES));
+
+    let res1 = part_1(&instructions, &network).ok_or("no path from AAA to ZZZ")?;
+    Day8::print_answer(1, res1);
+
+    let res2 = part_2(&instructions, &network)
+        .ok_or("ghosts never are all on Z nodes at the same time")?;
+    Day8::print_answer(2, res2);
+
+    // e.g. `cargo run -p day_8 -- --lcm`
+    if std::env::args().any(|arg| arg == "--lcm") {
+        let starts = network.starts().collect_vec();
+        let analysis = cycles::analyse(&instructions, &network, &starts);
+        for (&start, ghost) in starts.iter().zip(&analysis.ghosts) {
+            println!(
+                "Ghost from {}: cycle of {} steps after {} steps, Z nodes at steps {:?} then {:?}",
+                network.name(start),
+                ghost.cycle_length,
+                ghost.pre_period,
+                ghost.transient_hits,
+                ghost.cyclic_hits
+            );
+        }
+        println!(
+            "LCM shortcut: {:?} (valid for this input: {})",
+            analysis.lcm_shortcut, analysis.lcm_shortcut_valid
+        );
+        // Walking the ghosts up to the actual answer would take far too long for the real input
+        let max_steps = analysis
+            .lcm_shortcut
+            .unwrap_or(u64::MAX)
+            .min(BRUTE_FORCE_MAX_STEPS);
+        match network::brute_force(&network, &instructions, &starts, max_steps) {
+            Some(steps) => println!(
+                "Brute force: {} steps (agrees with the LCM shortcut: {})",
+                steps,
+                analysis.lcm_shortcut == Some(steps)
+            ),
+            None => println!(
+                "Brute force: not all on Z nodes within {} steps (agrees with the LCM shortcut: {})",
+                max_steps,
+                analysis.lcm_shortcut.is_none_or(|lcm| lcm > max_steps)
+            ),
+        }
+    }
 
     Ok(())
 }
 
-fn part_1(instructions: &[Instruction], nodes: &HashMap<Node, (Node, Node)>) -> usize {
-    let mut count = 0;
-    let mut current_node = nodes.keys().find(|n| n.0 == "AAA").unwrap();
-    let repeated_instructions = repeat(instructions).flat_map(|i| i.iter());
-    for instruction in repeated_instructions {
-        if current_node.0 == "ZZZ" {
-            return count;
+fn part_1(instructions: &[Instruction], network: &Network) -> Option<usize> {
+    let start = network.id("AAA")?;
+    let end = network.id("ZZZ")?;
+    // After visiting every (node, instruction) state, the path is bound to loop forever
+    let max_steps = network.len() * instructions.len();
+    let mut current_node = start;
+    for (count, &instruction) in instructions.iter().cycle().take(max_steps + 1).enumerate() {
+        if current_node == end {
+            return Some(count);
         }
-        count += 1;
-        let choices = nodes.get(current_node).unwrap();
-        current_node = instruction.apply(choices);
+        current_node = network.step(current_node, instruction);
     }
-    0 // never reached
+    None
 }
 
-fn part_2(instructions: &[Instruction], nodes: &HashMap<Node, (Node, Node)>) -> u64 {
-    let starting_nodes = nodes.keys().filter(|n| n.0.ends_with("A")).collect_vec();
-
-    let loop_periods = starting_nodes
-        .into_iter()
-        .map(|starting_node| {
-            let mut count = 0u64;
-            let mut current_node = starting_node;
-            let repeated_instructions = repeat(instructions).flat_map(|i| i.iter());
-            for instruction in repeated_instructions {
-                if current_node.0.ends_with("Z") {
-                    return count;
-                }
-                count += 1;
-                let choices = nodes.get(current_node).unwrap();
-                current_node = instruction.apply(choices);
-            }
-            0 // never reached
-        })
-        .collect_vec();
-
-    lcmx(&loop_periods).unwrap()
+/// Steps until all the ghosts are on Z nodes at the same time, without assuming anything about the
+/// shape of their paths (see `cycles::analyse`).
+fn part_2(instructions: &[Instruction], network: &Network) -> Option<u64> {
+    let starts = network.starts().collect_vec();
+    cycles::analyse(instructions, network, &starts).first_common_hit
 }
 
 fn parse_instructions(input: &str) -> Vec<Instruction> {
@@ -81,15 +112,6 @@ fn parse_nodes(input: &str) -> HashMap<Node, (Node, Node)> {
         .collect()
 }
 
-impl Instruction {
-    fn apply<'a>(&self, (l, r): &'a (Node, Node)) -> &'a Node {
-        match self {
-            Instruction::Left => l,
-            Instruction::Right => r,
-        }
-    }
-}
-
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 enum Instruction {
     Left,
@@ -123,20 +145,36 @@ XXX = (XXX, XXX)";
         assert_eq!(
             part_1(
                 &parse_instructions(TEST_INSTRUCTIONS_1),
-                &parse_nodes(TEST_NODES_1)
+                &Network::compile(&parse_nodes(TEST_NODES_1))
             ),
-            6
+            Some(6)
         );
     }
 
     #[test]
     fn test_part_2() {
+        let instructions = parse_instructions(TEST_INSTRUCTIONS_2);
+        let network = Network::compile(&parse_nodes(TEST_NODES_2));
+        let starts = network.starts().collect_vec();
+        let analysis = cycles::analyse(&instructions, &network, &starts);
+        assert_eq!(analysis.lcm_shortcut, Some(6));
+        assert!(analysis.lcm_shortcut_valid);
+
+        assert_eq!(part_2(&instructions, &network), Some(6));
         assert_eq!(
-            part_2(
-                &parse_instructions(TEST_INSTRUCTIONS_2),
-                &parse_nodes(TEST_NODES_2)
-            ),
-            6
+            network::brute_force(&network, &instructions, &starts, 100),
+            Some(6)
+        );
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!(
+            "test_input_2",
+            (
+                parse_instructions(TEST_INSTRUCTIONS_2),
+                parse_nodes(TEST_NODES_2)
+            )
         );
     }
 }
@@ -0,0 +1,111 @@
+use crate::{Instruction, Node};
+use std::collections::HashMap;
+
+pub type NodeId = u32;
+
+/// The nodes, interned to dense ids (in the alphabetical order of their names), so that walking
+/// the network needs neither hashing nor string comparisons.
+pub struct Network {
+    names: Vec<String>,
+    left: Vec<NodeId>,
+    right: Vec<NodeId>,
+    starts: BitSet,
+    ends: BitSet,
+}
+
+impl Network {
+    pub fn compile(nodes: &HashMap<Node, (Node, Node)>) -> Network {
+        let mut names = nodes.keys().map(|n| n.0.clone()).collect::<Vec<_>>();
+        names.sort_unstable();
+        let ids = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.as_str(), id as NodeId))
+            .collect::<HashMap<_, _>>();
+        let (left, right) = names
+            .iter()
+            .map(|name| {
+                let (l, r) = &nodes[&Node(name.clone())];
+                (ids[l.0.as_str()], ids[r.0.as_str()])
+            })
+            .unzip();
+        let starts = BitSet::from_fn(names.len(), |id| names[id].ends_with('A'));
+        let ends = BitSet::from_fn(names.len(), |id| names[id].ends_with('Z'));
+        Network { names, left, right, starts, ends }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.names
+            .binary_search_by(|n| n.as_str().cmp(name))
+            .ok()
+            .map(|id| id as NodeId)
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id as usize]
+    }
+
+    #[inline]
+    pub fn step(&self, node: NodeId, instruction: Instruction) -> NodeId {
+        match instruction {
+            Instruction::Left => self.left[node as usize],
+            Instruction::Right => self.right[node as usize],
+        }
+    }
+
+    #[inline]
+    pub fn is_end(&self, node: NodeId) -> bool {
+        self.ends.contains(node)
+    }
+
+    pub fn starts(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.len() as NodeId).filter(|&id| self.starts.contains(id))
+    }
+}
+
+/// Steps until all the ghosts are on Z nodes at the same time, by walking them all step by step
+/// (`None` if it takes more than `max_steps`).
+pub fn brute_force(
+    network: &Network,
+    instructions: &[Instruction],
+    starts: &[NodeId],
+    max_steps: u64,
+) -> Option<u64> {
+    let mut ghosts = starts.to_vec();
+    let mut instruction_idx = 0;
+    for step in 0..=max_steps {
+        if ghosts.iter().all(|&g| network.is_end(g)) {
+            return Some(step);
+        }
+        let instruction = instructions[instruction_idx];
+        for ghost in ghosts.iter_mut() {
+            *ghost = network.step(*ghost, instruction);
+        }
+        instruction_idx += 1;
+        if instruction_idx == instructions.len() {
+            instruction_idx = 0;
+        }
+    }
+    None
+}
+
+struct BitSet(Vec<u64>);
+
+impl BitSet {
+    fn from_fn(len: usize, f: impl Fn(usize) -> bool) -> BitSet {
+        let mut words = vec![0u64; len.div_ceil(64)];
+        for i in (0..len).filter(|&i| f(i)) {
+            words[i / 64] |= 1 << (i % 64);
+        }
+        BitSet(words)
+    }
+
+    #[inline]
+    fn contains(&self, i: NodeId) -> bool {
+        self.0[i as usize / 64] & (1 << (i % 64)) != 0
+    }
+}
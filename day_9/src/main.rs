@@ -1,39 +1,60 @@
+use crate::polynomial::{FitError, Polynomial};
 use common::itertools::Itertools;
+use common::num_bigint::BigInt;
+use common::Solution;
+
+mod polynomial;
 
 static INPUT: &str = include_str!("input");
 
+struct Day9;
+
+impl Solution for Day9 {}
+
 fn main() {
     let values = parse_input(INPUT);
 
-    let (res1, res2) = part_1_and_2(&values);
-    println!("Part 1 result: {}", res1);
-    println!("Part 2 result: {}", res2);
+    let (res1, res2) = part_1_and_2(&values).expect("cannot extrapolate the values");
+    Day9::print_answer(1, res1);
+    Day9::print_answer(2, res2);
+
+    // e.g. `--horizon 1000` to extrapolate 1000 steps in both directions
+    let args = std::env::args().collect_vec();
+    if let Some(horizon) = args.iter().skip_while(|&a| a != "--horizon").nth(1) {
+        let steps = horizon.parse::<u64>().expect("invalid horizon");
+        let polynomials = fit_all(&values).expect("cannot extrapolate the values");
+        let max_degree = polynomials.iter().filter_map(|p| p.degree()).max();
+        println!("Max degree: {:?}", max_degree);
+        println!(
+            "Sum {} steps forward: {}",
+            steps,
+            extrapolate(&polynomials, |p| p.next(steps))
+        );
+        println!(
+            "Sum {} steps backward: {}",
+            steps,
+            extrapolate(&polynomials, |p| p.previous(steps))
+        );
+    }
+}
+
+fn part_1_and_2(values_list: &[Vec<i64>]) -> Result<(BigInt, BigInt), FitError> {
+    let polynomials = fit_all(values_list)?;
+    let res1 = extrapolate(&polynomials, |p| p.next(1));
+    let res2 = extrapolate(&polynomials, |p| p.previous(1));
+    Ok((res1, res2))
 }
 
-fn part_1_and_2(values_list: &[Vec<i64>]) -> (i64, i64) {
+fn fit_all(values_list: &[Vec<i64>]) -> Result<Vec<Polynomial>, FitError> {
     values_list
         .iter()
-        .map(|values| {
-            let mut last_numbers = Vec::new();
-            let mut first_numbers = Vec::new();
-            let mut curr_values = values.clone();
-            while curr_values.iter().any(|&v| v != 0) {
-                last_numbers.push(*curr_values.last().unwrap());
-                first_numbers.push(*curr_values.first().unwrap());
-                curr_values = curr_values
-                    .iter()
-                    .tuple_windows::<(_, _)>()
-                    .map(|(&a, &b)| b - a)
-                    .collect_vec();
-            }
-            (
-                last_numbers.iter().rev().fold(0, |prev, next| next + prev),
-                first_numbers.iter().rev().fold(0, |prev, next| next - prev),
-            )
-        })
-        .fold((0, 0), |(acc1, acc2), (next1, next2)| {
-            (acc1 + next1, acc2 + next2)
-        })
+        .map(|values| Polynomial::fit(values))
+        .collect()
+}
+
+/// Sum of the extrapolated values
+fn extrapolate(polynomials: &[Polynomial], f: impl Fn(&Polynomial) -> BigInt) -> BigInt {
+    polynomials.iter().map(f).sum()
 }
 
 fn parse_input(input: &str) -> Vec<Vec<i64>> {
@@ -57,6 +78,14 @@ mod tests {
 
     #[test]
     fn test_part_1_and_2() {
-        assert_eq!(part_1_and_2(&parse_input(TEST_INPUT)), (114, 2));
+        assert_eq!(
+            part_1_and_2(&parse_input(TEST_INPUT)),
+            Ok((BigInt::from(114), BigInt::from(2)))
+        );
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        common::assert_snapshot!("test_input", parse_input(TEST_INPUT));
     }
 }
@@ -0,0 +1,132 @@
+use common::num_bigint::BigInt;
+use common::num_traits::{One, Zero};
+
+/// Polynomial through the values of a sequence (taken at x = 0, 1, 2...), in Newton forward form:
+/// `f(x) = Σ Δᵏf(0) · C(x, k)`. Since `C(x, k)` is an integer for any integer `x` (even negative),
+/// evaluating it at any step is exact with integer arithmetic only.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Polynomial {
+    /// `Δᵏf(0)` for each k, without the trailing zeros
+    differences: Vec<i128>,
+    /// Number of values the polynomial was fitted on
+    len: usize,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum FitError {
+    Empty,
+    /// The differences never reach a row of zeros, so nothing proves that the polynomial of
+    /// lowest degree going through the values is actually the one which generated them
+    NotConverging(Polynomial),
+}
+
+impl Polynomial {
+    pub fn fit(values: &[i64]) -> Result<Polynomial, FitError> {
+        if values.is_empty() {
+            return Err(FitError::Empty);
+        }
+        let mut differences = Vec::with_capacity(values.len());
+        let mut row = values.iter().map(|&v| v as i128).collect::<Vec<_>>();
+        while !row.is_empty() && row.iter().any(|&v| v != 0) {
+            differences.push(row[0]);
+            row = row.windows(2).map(|w| w[1] - w[0]).collect();
+        }
+        let polynomial = Polynomial { differences, len: values.len() };
+        if row.is_empty() {
+            Err(FitError::NotConverging(polynomial))
+        } else {
+            Ok(polynomial)
+        }
+    }
+
+    /// `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.differences.len().checked_sub(1)
+    }
+
+    pub fn value_at(&self, x: i128) -> BigInt {
+        let mut value = BigInt::zero();
+        let mut binomial = BigInt::one(); // C(x, k)
+        for (k, &difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                // C(x, k) = C(x, k - 1) · (x - k + 1) / k, and the division is exact
+                binomial = binomial * (x - k as i128 + 1) / k;
+            }
+            value += &binomial * difference;
+        }
+        value
+    }
+
+    /// Value `steps` steps after the last value of the sequence.
+    pub fn next(&self, steps: u64) -> BigInt {
+        self.value_at(self.len as i128 - 1 + steps as i128)
+    }
+
+    /// Value `steps` steps before the first value of the sequence.
+    pub fn previous(&self, steps: u64) -> BigInt {
+        self.value_at(-(steps as i128))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        let polynomial = Polynomial::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(polynomial.degree(), Some(3));
+        assert_eq!(polynomial.next(1), BigInt::from(68));
+        assert_eq!(polynomial.previous(1), BigInt::from(5));
+        assert_eq!(
+            (0..6).map(|x| polynomial.value_at(x)).collect::<Vec<_>>(),
+            [10, 13, 16, 21, 30, 45].map(BigInt::from)
+        );
+
+        let polynomial = Polynomial::fit(&[0, 0, 0]).unwrap();
+        assert_eq!(polynomial.degree(), None);
+        assert_eq!(polynomial.next(1_000_000), BigInt::from(0));
+
+        assert_eq!(Polynomial::fit(&[]), Err(FitError::Empty));
+    }
+
+    #[test]
+    fn test_far_horizons() {
+        // x² - 3x + 7
+        let f = |x: i128| x * x - 3 * x + 7;
+        let values = (0..5).map(|x| f(x) as i64).collect::<Vec<_>>();
+        let polynomial = Polynomial::fit(&values).unwrap();
+        assert_eq!(polynomial.degree(), Some(2));
+        for steps in [1, 2, 10, 12345, 1_000_000_000_000_000_000] {
+            assert_eq!(polynomial.next(steps), BigInt::from(f(4 + steps as i128)));
+            assert_eq!(
+                polynomial.previous(steps),
+                BigInt::from(f(-(steps as i128)))
+            );
+        }
+
+        // Far beyond what fits in an i128
+        let values = (0..7).map(|x: i64| x.pow(5)).collect::<Vec<_>>();
+        let polynomial = Polynomial::fit(&values).unwrap();
+        assert_eq!(polynomial.degree(), Some(5));
+        assert_eq!(polynomial.previous(3), BigInt::from(-243));
+        let x: BigInt = BigInt::from(u64::MAX) + 6;
+        assert_eq!(polynomial.next(u64::MAX), x.pow(5));
+    }
+
+    #[test]
+    fn test_not_converging() {
+        // Degree 2 would be needed, but there aren't enough values to confirm it
+        let Err(FitError::NotConverging(polynomial)) = Polynomial::fit(&[1, 2, 4]) else {
+            panic!("should not converge");
+        };
+        assert_eq!(polynomial.degree(), Some(2));
+        assert_eq!(polynomial.next(1), BigInt::from(7));
+
+        assert!(matches!(
+            Polynomial::fit(&[5]),
+            Err(FitError::NotConverging(_))
+        ));
+        assert!(Polynomial::fit(&[5, 5]).is_ok());
+    }
+}
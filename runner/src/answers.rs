@@ -0,0 +1,148 @@
+use crate::days::{DayRun, Outcome};
+use common::itertools::Itertools;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+const HEADER: &str = "\
+# Answers accepted on adventofcode.com, one `<day> <part> <answer>` per line.
+# Record the current answers with `cargo run -p runner -- --record` once they're accepted.
+";
+
+/// Known correct answers, by (day, part)
+#[derive(Default)]
+pub struct Answers(BTreeMap<(u8, u8), String>);
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum Verification {
+    Correct,
+    Wrong { expected: String },
+    Unknown,
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Answers {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Answers::default();
+        };
+        Answers::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Answers {
+        let answers = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let mut fields = line.splitn(3, ' ');
+                let mut number = || fields.next().and_then(|f| f.parse::<u8>().ok());
+                let key = number().zip(number());
+                let answer = fields.next();
+                match (key, answer) {
+                    (Some(key), Some(answer)) => (key, answer.trim().to_owned()),
+                    _ => panic!("Invalid answer line: {}", line),
+                }
+            })
+            .collect();
+        Answers(answers)
+    }
+
+    pub fn verify(&self, day: u8, part: u8, answer: &str) -> Verification {
+        match self.0.get(&(day, part)) {
+            Some(expected) if expected == answer => Verification::Correct,
+            Some(expected) => Verification::Wrong { expected: expected.clone() },
+            None => Verification::Unknown,
+        }
+    }
+
+    /// Replace the answers of the days which ran with their current answers (except placeholder
+    /// answers)
+    pub fn record(&mut self, runs: &[DayRun]) {
+        for run in runs {
+            if let Outcome::Ran { parts, .. } = &run.outcome {
+                for part in parts.iter().filter(|part| !part.stub) {
+                    self.0.insert((run.day, part.part), part.answer.clone());
+                }
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        let lines = self
+            .0
+            .iter()
+            .map(|((day, part), answer)| format!("{} {} {}\n", day, part, answer))
+            .join("");
+        fs::write(path, format!("{}{}", HEADER, lines))
+            .unwrap_or_else(|e| panic!("Cannot write {}: {}", path.display(), e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::PartResult;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    /// Same format as `runner/answers`, with the answers of day 6
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join("answers")
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::parse(&format!("{}\n6 1 1624896\n6 2 32583852\n", HEADER));
+        assert_eq!(answers.verify(6, 1, "1624896"), Verification::Correct);
+        assert_eq!(
+            answers.verify(6, 2, "1"),
+            Verification::Wrong { expected: "32583852".to_owned() }
+        );
+        assert_eq!(answers.verify(7, 1, "1"), Verification::Unknown);
+    }
+
+    #[test]
+    fn test_load_fixture() {
+        let answers = Answers::load(&fixture());
+        assert_eq!(answers.verify(6, 1, "1624896"), Verification::Correct);
+        assert_eq!(answers.verify(6, 2, "32583852"), Verification::Correct);
+        assert_eq!(answers.verify(17, 1, "1"), Verification::Unknown);
+    }
+
+    #[test]
+    fn test_record_and_save() {
+        let part = |part, answer: &str, stub| PartResult {
+            part,
+            answer: answer.into(),
+            elapsed: None,
+            stub,
+        };
+        let runs = vec![
+            DayRun {
+                day: 6,
+                outcome: Outcome::Ran {
+                    parts: vec![part(1, "1624896", false), part(2, "32583852", false)],
+                    elapsed: Duration::from_millis(3),
+                },
+            },
+            DayRun {
+                day: 17,
+                outcome: Outcome::Ran {
+                    parts: vec![part(1, "1", true), part(2, "1", true)],
+                    elapsed: Duration::from_micros(500),
+                },
+            },
+            DayRun { day: 22, outcome: Outcome::BuildFailed },
+        ];
+        let mut answers = Answers::default();
+        answers.record(&runs);
+
+        let path = std::env::temp_dir().join(format!("runner-answers-{}", std::process::id()));
+        answers.save(&path);
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved, fs::read_to_string(fixture()).unwrap());
+    }
+}
@@ -0,0 +1,161 @@
+use common::lazy_static::lazy_static;
+use common::regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+pub const DAYS: std::ops::RangeInclusive<u8> = 1..=25;
+
+lazy_static! {
+    static ref EXECUTABLE: Regex = Regex::new(r#""executable":"([^"]+)""#).unwrap();
+    static ref RESULT: Regex = Regex::new(r"^Part (\d+) result: (.*)$").unwrap();
+    static ref ELAPSED: Regex = Regex::new(r"^(?i:part) (\d+) elapsed time: (.*)$").unwrap();
+    static ref STUB: Regex = Regex::new(r"^Part (\d+) is a stub$").unwrap();
+}
+
+pub struct DayRun {
+    pub day: u8,
+    pub outcome: Outcome,
+}
+
+pub enum Outcome {
+    BuildFailed,
+    /// The binary didn't exit successfully: exit status and last line of its error output
+    Failed(String),
+    Ran {
+        parts: Vec<PartResult>,
+        /// Whole run of the binary, including the parsing of the input
+        elapsed: Duration,
+    },
+}
+
+pub struct PartResult {
+    pub part: u8,
+    pub answer: String,
+    /// As printed by `common::time_execution`, if the day times its parts
+    pub elapsed: Option<String>,
+    /// Placeholder answer, according to the day's `common::Solution` implementation
+    pub stub: bool,
+}
+
+/// Build the binaries of the days in release mode, with a single cargo invocation (a day which
+/// doesn't compile doesn't prevent the others from being built). Returns the path of each binary
+/// which could be built.
+pub fn build(workspace: &Path, days: &[u8]) -> HashMap<u8, PathBuf> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+    let mut command = Command::new(cargo);
+    command.current_dir(workspace).args([
+        "build",
+        "--release",
+        "--keep-going",
+        "--message-format=json",
+    ]);
+    for day in days {
+        command.arg("-p").arg(package(*day));
+    }
+    // Errors are reported as missing binaries
+    let output = command.output().expect("cannot run cargo");
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| EXECUTABLE.captures(line))
+        .filter_map(|captures| {
+            let path = PathBuf::from(&captures[1]);
+            let name = path.file_stem()?.to_str()?.to_owned();
+            let day = days.iter().find(|&&day| package(day) == name)?;
+            Some((*day, path))
+        })
+        .collect()
+}
+
+pub fn package(day: u8) -> String {
+    format!("day_{}", day)
+}
+
+pub fn run(day: u8, executable: Option<&Path>) -> DayRun {
+    let Some(executable) = executable else {
+        return DayRun { day, outcome: Outcome::BuildFailed };
+    };
+    let before = Instant::now();
+    let output = Command::new(executable).output();
+    let elapsed = before.elapsed();
+    let outcome = match output {
+        Err(e) => Outcome::Failed(e.to_string()),
+        Ok(output) if !output.status.success() => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let last_line = stderr.lines().rfind(|l| !l.trim().is_empty());
+            Outcome::Failed(format!("{} {}", output.status, last_line.unwrap_or("")))
+        }
+        Ok(output) => Outcome::Ran {
+            parts: parse_output(&String::from_utf8_lossy(&output.stdout)),
+            elapsed,
+        },
+    };
+    DayRun { day, outcome }
+}
+
+/// The answers printed by `common::Solution::print_answer` (as `Part <n> result: <answer>`,
+/// followed by `Part <n> is a stub` for placeholder answers), with their times if also printed by
+/// `common::time_execution` (as `part <n> elapsed time: <time>`)
+fn parse_output(stdout: &str) -> Vec<PartResult> {
+    let mut parts: Vec<PartResult> = Vec::new();
+    let mut times = HashMap::new();
+    let mut stubs = Vec::new();
+    for line in stdout.lines() {
+        if let Some(captures) = RESULT.captures(line) {
+            parts.push(PartResult {
+                part: captures[1].parse().unwrap(),
+                answer: captures[2].trim().to_owned(),
+                elapsed: None,
+                stub: false,
+            });
+        } else if let Some(captures) = STUB.captures(line) {
+            stubs.push(captures[1].parse::<u8>().unwrap());
+        } else if let Some(captures) = ELAPSED.captures(line) {
+            times.insert(
+                captures[1].parse::<u8>().unwrap(),
+                captures[2].trim().to_owned(),
+            );
+        }
+    }
+    for part in &mut parts {
+        part.elapsed = times.remove(&part.part);
+        part.stub = stubs.contains(&part.part);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let parts = parse_output(
+            "part 1 elapsed time: 12.50µs
+Part 1 result: 1624896
+Part 2 result: 32583852
+Part 2 is a stub
+Unfolded x20 result: 1",
+        );
+        assert_eq!(parts.len(), 2);
+        assert_eq!((parts[0].stub, parts[1].stub), (false, true));
+        assert_eq!(
+            (
+                parts[0].part,
+                parts[0].answer.as_str(),
+                parts[0].elapsed.as_deref()
+            ),
+            (1, "1624896", Some("12.50µs"))
+        );
+        assert_eq!(
+            (
+                parts[1].part,
+                parts[1].answer.as_str(),
+                parts[1].elapsed.as_deref()
+            ),
+            (2, "32583852", None)
+        );
+    }
+}
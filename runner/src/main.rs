@@ -0,0 +1,116 @@
+mod answers;
+mod days;
+mod report;
+mod table;
+
+use crate::answers::Answers;
+use crate::days::{DayRun, DAYS};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Build and run every day (or only the days given as arguments) concurrently, then print their
+/// answers and times in a single table, e.g.
+/// `cargo run -p runner -- --markdown --jobs 4 1 2 3`
+///
+/// The times are measured while the other days are running, so they are only indicative.
+///
+/// With `--report`, all the days are run one at a time instead, and their progress (implemented
+/// parts, example tests, verified answers and times) is written to the README.
+fn main() {
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--markdown" => options.markdown = true,
+            "--record" => options.record = true,
+            "--report" => options.report = true,
+            "--jobs" => {
+                let jobs = args
+                    .next()
+                    .and_then(|jobs| jobs.parse().ok())
+                    .filter(|&jobs| jobs > 0)
+                    .expect("--jobs expects a positive number");
+                options.jobs = Some(jobs);
+            }
+            _ => match arg.parse::<u8>() {
+                Ok(day) if DAYS.contains(&day) => options.days.push(day),
+                _ => eprintln!("Unknown argument {}", arg),
+            },
+        }
+    }
+    if options.report && !options.days.is_empty() {
+        eprintln!("--report always runs all the days");
+        options.days.clear();
+    }
+    if options.days.is_empty() {
+        options.days = DAYS.collect();
+    }
+    let jobs = match options.jobs {
+        Some(jobs) => jobs,
+        // Concurrent runs would slow each other down
+        None if options.report => 1,
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let answers_path = workspace.join("runner").join("answers");
+    let mut answers = Answers::load(&answers_path);
+
+    eprintln!("Building {} days...", options.days.len());
+    let executables = days::build(workspace, &options.days);
+    let runs = run_all(&options.days, &executables, jobs);
+
+    if options.record {
+        answers.record(&runs);
+        answers.save(&answers_path);
+    }
+    if options.report {
+        let report = report::render(&runs, &answers, workspace);
+        report::update_readme(&workspace.join("README.md"), &report);
+        print!("{}", report);
+        return;
+    }
+    let rows = table::rows(&runs, &answers);
+    if options.markdown {
+        print!("{}", table::render_markdown(&rows));
+    } else {
+        print!("{}", table::render_text(&rows));
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    days: Vec<u8>,
+    /// Number of days running at the same time, by default one per CPU (or one for a report)
+    jobs: Option<usize>,
+    markdown: bool,
+    record: bool,
+    report: bool,
+}
+
+/// Run the days on a pool of `jobs` threads, each taking the next day to run until there is none
+/// left. The results are in the same order as `days`.
+fn run_all(
+    days: &[u8],
+    executables: &std::collections::HashMap<u8, PathBuf>,
+    jobs: usize,
+) -> Vec<DayRun> {
+    let next = AtomicUsize::new(0);
+    let runs = Mutex::new(Vec::with_capacity(days.len()));
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(days.len()) {
+            scope.spawn(|| {
+                while let Some(&day) = days.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let run = days::run(day, executables.get(&day).map(PathBuf::as_path));
+                    eprintln!("Day {} done", day);
+                    runs.lock().unwrap().push(run);
+                }
+            });
+        }
+    });
+    let mut runs = runs.into_inner().unwrap();
+    runs.sort_by_key(|run| days.iter().position(|&day| day == run.day));
+    runs
+}
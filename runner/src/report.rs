@@ -0,0 +1,195 @@
+use crate::answers::{Answers, Verification};
+use crate::days::{package, DayRun, Outcome};
+use common::itertools::Itertools;
+use common::lazy_static::lazy_static;
+use common::regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+const START: &str = "<!-- progress -->";
+const END: &str = "<!-- /progress -->";
+const PARTS: usize = 2;
+
+lazy_static! {
+    static ref EXAMPLE_TEST: Regex = Regex::new(r"fn test_part_(\d+)(?:_and_(\d+))?").unwrap();
+}
+
+/// Parts covered by tests on the puzzle examples (`test_part_<n>` or `test_part_<n>_and_<m>`) in
+/// the sources of the day
+pub fn example_tests(workspace: &Path, day: u8) -> BTreeSet<u8> {
+    let Ok(entries) = fs::read_dir(workspace.join(package(day)).join("src")) else {
+        return BTreeSet::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rs"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .flat_map(|source| parse_example_tests(&source))
+        .collect()
+}
+
+fn parse_example_tests(source: &str) -> BTreeSet<u8> {
+    EXAMPLE_TEST
+        .captures_iter(source)
+        .flat_map(|captures| {
+            let parts = [captures.get(1), captures.get(2)];
+            parts
+                .into_iter()
+                .flatten()
+                .map(|part| part.as_str().parse().unwrap())
+        })
+        .collect()
+}
+
+/// Markdown progress table, one row per day, preceded by a summary line
+pub fn render(runs: &[DayRun], answers: &Answers, workspace: &Path) -> String {
+    let (mut implemented, mut verified) = (0, 0);
+    let mut rows = Vec::new();
+    for run in runs {
+        let statuses: [String; PARTS] = std::array::from_fn(|i| {
+            let status = part_status(run, i as u8 + 1, answers);
+            implemented += usize::from(matches!(status, "solved" | "verified" | "wrong"));
+            verified += usize::from(status == "verified");
+            status.to_owned()
+        });
+        let tests = example_tests(workspace, run.day);
+        let tests = if tests.is_empty() {
+            "-".to_owned()
+        } else {
+            tests.iter().join(", ")
+        };
+        let time = match &run.outcome {
+            Outcome::Ran { elapsed, .. } => format!("{:.2?}", elapsed),
+            _ => "".to_owned(),
+        };
+        rows.push(format!(
+            "| {} | {} | {} | {} |\n",
+            run.day,
+            statuses.join(" | "),
+            tests,
+            time
+        ));
+    }
+    format!(
+        "{} of {} parts implemented, {} verified.\n\n\
+         | Day | Part 1 | Part 2 | Example tests | Time |\n\
+         | ---: | --- | --- | --- | ---: |\n{}",
+        implemented,
+        runs.len() * PARTS,
+        verified,
+        rows.concat()
+    )
+}
+
+fn part_status(run: &DayRun, part: u8, answers: &Answers) -> &'static str {
+    match &run.outcome {
+        Outcome::BuildFailed => "build failed",
+        Outcome::Failed(_) => "failed",
+        Outcome::Ran { parts, .. } => match parts.iter().find(|p| p.part == part) {
+            None => "-",
+            Some(p) if p.stub => "stub",
+            Some(p) => match answers.verify(run.day, part, &p.answer) {
+                Verification::Correct => "verified",
+                Verification::Wrong { .. } => "wrong",
+                Verification::Unknown => "solved",
+            },
+        },
+    }
+}
+
+/// Replace the progress section of the README (between `<!-- progress -->` and
+/// `<!-- /progress -->`) with the report, or append it if there is none yet
+pub fn update_readme(path: &Path, report: &str) {
+    let readme = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Cannot read {}: {}", path.display(), e));
+    fs::write(path, with_report(&readme, report))
+        .unwrap_or_else(|e| panic!("Cannot write {}: {}", path.display(), e));
+}
+
+fn with_report(readme: &str, report: &str) -> String {
+    let section = format!("{}\n{}{}", START, report, END);
+    match (readme.find(START), readme.find(END)) {
+        (Some(start), Some(end)) if start < end => {
+            format!(
+                "{}{}{}",
+                &readme[..start],
+                section,
+                &readme[end + END.len()..]
+            )
+        }
+        _ => format!("{}\n## Progress\n\n{}\n", readme, section),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::PartResult;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_example_tests() {
+        let source = "
+    #[test]
+    fn test_part_1_input_2() {}
+    fn test_part_1_and_2() {}
+    fn test_parse() {}";
+        assert_eq!(parse_example_tests(source), BTreeSet::from([1, 2]));
+        assert!(parse_example_tests("fn test_parse() {}").is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let part = |part, answer: &str, stub| PartResult {
+            part,
+            answer: answer.into(),
+            elapsed: None,
+            stub,
+        };
+        let runs = vec![
+            DayRun {
+                day: 6,
+                outcome: Outcome::Ran {
+                    parts: vec![part(1, "1624896", false), part(2, "1", false)],
+                    elapsed: Duration::from_millis(3),
+                },
+            },
+            DayRun {
+                day: 17,
+                outcome: Outcome::Ran {
+                    parts: vec![part(1, "1", true), part(2, "1", true)],
+                    elapsed: Duration::from_micros(500),
+                },
+            },
+            DayRun { day: 22, outcome: Outcome::BuildFailed },
+        ];
+        let answers = Answers::parse("6 1 1624896\n6 2 32583852\n");
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        assert_eq!(
+            render(&runs, &answers, workspace),
+            "2 of 6 parts implemented, 1 verified.
+
+| Day | Part 1 | Part 2 | Example tests | Time |
+| ---: | --- | --- | --- | ---: |
+| 6 | verified | wrong | 1, 2 | 3.00ms |
+| 17 | stub | stub | - | 500.00µs |
+| 22 | build failed | build failed | - |  |
+"
+        );
+    }
+
+    #[test]
+    fn test_with_report() {
+        let readme = "# Title\n";
+        let updated = with_report(readme, "first\n");
+        assert_eq!(
+            updated,
+            "# Title\n\n## Progress\n\n<!-- progress -->\nfirst\n<!-- /progress -->\n"
+        );
+        assert_eq!(
+            with_report(&updated, "second\n"),
+            "# Title\n\n## Progress\n\n<!-- progress -->\nsecond\n<!-- /progress -->\n"
+        );
+    }
+}
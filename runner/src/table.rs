@@ -0,0 +1,166 @@
+use crate::answers::{Answers, Verification};
+use crate::days::{DayRun, Outcome};
+use common::itertools::Itertools;
+
+const HEADERS: [&str; 5] = ["Day", "Part", "Answer", "Time", "Verified"];
+/// Whether each column is aligned to the right
+const RIGHT_ALIGNED: [bool; 5] = [true, true, false, true, false];
+
+/// One row per part (or per day, for days which didn't run)
+pub fn rows(runs: &[DayRun], answers: &Answers) -> Vec<[String; 5]> {
+    let mut rows = Vec::new();
+    for run in runs {
+        let day = run.day.to_string();
+        match &run.outcome {
+            Outcome::BuildFailed => {
+                rows.push([day, "-".into(), "build failed".into(), "".into(), "".into()]);
+            }
+            Outcome::Failed(error) => {
+                let answer = format!("failed: {}", error);
+                rows.push([day, "-".into(), answer, "".into(), "".into()]);
+            }
+            Outcome::Ran { parts, elapsed } if parts.is_empty() => {
+                let time = format!("{:.2?}", elapsed);
+                rows.push([day, "-".into(), "no answer".into(), time, "".into()]);
+            }
+            Outcome::Ran { parts, elapsed } => {
+                for part in parts {
+                    let time = match &part.elapsed {
+                        Some(time) => time.clone(),
+                        None => format!("{:.2?} (day)", elapsed),
+                    };
+                    let verification = answers.verify(run.day, part.part, &part.answer);
+                    let verified = match verification {
+                        _ if part.stub => "stub".to_owned(),
+                        Verification::Correct => "yes".to_owned(),
+                        Verification::Wrong { expected } => format!("NO (expected {})", expected),
+                        Verification::Unknown => "?".to_owned(),
+                    };
+                    let row = [
+                        day.clone(),
+                        part.part.to_string(),
+                        part.answer.clone(),
+                        time,
+                        verified,
+                    ];
+                    rows.push(row);
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// Plain text table, with aligned columns
+pub fn render_text(rows: &[[String; 5]]) -> String {
+    let widths: [usize; 5] = std::array::from_fn(|col| {
+        rows.iter()
+            .map(|row| row[col].chars().count())
+            .chain([HEADERS[col].len()])
+            .max()
+            .unwrap()
+    });
+    let line = |cells: [&str; 5]| {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(col, cell)| {
+                if RIGHT_ALIGNED[col] {
+                    format!("{:>width$}", cell, width = widths[col])
+                } else {
+                    format!("{:<width$}", cell, width = widths[col])
+                }
+            })
+            .join("  ")
+            .trim_end()
+            .to_owned()
+    };
+    let separator = widths.map(|width| "-".repeat(width));
+    std::iter::once(line(HEADERS))
+        .chain(std::iter::once(line(
+            separator.each_ref().map(String::as_str),
+        )))
+        .chain(
+            rows.iter()
+                .map(|row| line(row.each_ref().map(String::as_str))),
+        )
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// GitHub flavoured Markdown table
+pub fn render_markdown(rows: &[[String; 5]]) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let alignments = RIGHT_ALIGNED
+        .iter()
+        .map(|&right| if right { "---:" } else { "---" }.to_owned())
+        .collect_vec();
+    std::iter::once(line(HEADERS.map(str::to_owned).to_vec()))
+        .chain(std::iter::once(line(alignments)))
+        .chain(
+            rows.iter()
+                .map(|row| line(row.iter().map(|cell| cell.replace('|', "\\|")).collect())),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::PartResult;
+    use std::time::Duration;
+
+    fn runs() -> Vec<DayRun> {
+        vec![
+            DayRun {
+                day: 6,
+                outcome: Outcome::Ran {
+                    parts: vec![
+                        PartResult {
+                            part: 1,
+                            answer: "1624896".into(),
+                            elapsed: Some("1.00µs".into()),
+                            stub: false,
+                        },
+                        PartResult {
+                            part: 2,
+                            answer: "32583852".into(),
+                            elapsed: None,
+                            stub: true,
+                        },
+                    ],
+                    elapsed: Duration::from_millis(3),
+                },
+            },
+            DayRun { day: 22, outcome: Outcome::BuildFailed },
+        ]
+    }
+
+    #[test]
+    fn test_render_text() {
+        let answers = Answers::default();
+        assert_eq!(
+            render_text(&rows(&runs(), &answers)),
+            "Day  Part  Answer                Time  Verified
+---  ----  ------------  ------------  --------
+  6     1  1624896             1.00µs  ?
+  6     2  32583852      3.00ms (day)  stub
+ 22     -  build failed
+"
+        );
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let answers = Answers::default();
+        assert_eq!(
+            render_markdown(&rows(&runs(), &answers)),
+            "| Day | Part | Answer | Time | Verified |
+| ---: | ---: | --- | ---: | --- |
+| 6 | 1 | 1624896 | 1.00µs | ? |
+| 6 | 2 | 32583852 | 3.00ms (day) | stub |
+| 22 | - | build failed |  |  |
+"
+        );
+    }
+}